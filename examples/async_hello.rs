@@ -0,0 +1,52 @@
+use std::str;
+
+use failure::Fallible;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use tipc::{aio::Async, Instance, ServiceAddr, Stream, Type};
+
+const SERVER_TYPE: Type = 18888;
+const SERVER_INST: Instance = 17;
+
+const BUF_SIZE: usize = 40;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Fallible<()> {
+    println!("****** TIPC async hello world started ******");
+
+    let srv = ServiceAddr::new(SERVER_TYPE, SERVER_INST);
+    let listener = Async::new(tipc::bind::<Stream, _>(srv)?.listen()?)?;
+
+    let server = tokio::spawn(async move {
+        let (mut conn, addr) = listener.accept().await?;
+        println!("Server: accepted connection from {}", addr);
+
+        let mut buf = [0; BUF_SIZE];
+        let len = conn.read(&mut buf[..]).await?;
+        println!(
+            "Server: Message received: {}",
+            String::from_utf8_lossy(&buf[..len])
+        );
+
+        conn.write_all(b"Uh ?").await?;
+
+        Ok::<_, std::io::Error>(())
+    });
+
+    let mut conn = Async::new(tipc::connect::<Stream, _>(srv)?)?;
+
+    let msg = "Hello World!!!";
+    conn.write_all(msg.as_bytes()).await?;
+    println!("Client: sent message: {}", msg);
+
+    let mut buf = [0; BUF_SIZE];
+    let len = conn.read(&mut buf[..]).await?;
+    let msg = str::from_utf8(&buf[..len])?;
+    println!("Client: received response: {}", msg);
+
+    server.await??;
+
+    println!("****** TIPC async hello world finished ******");
+
+    Ok(())
+}
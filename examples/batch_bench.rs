@@ -0,0 +1,138 @@
+//! A benchmark comparing the batched `send_batch`/`recv_batch` with the per-message loop on `SOCK_RDM` sockets.
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use failure::Fallible;
+use structopt::StructOpt;
+
+use tipc::{Bound, Datagram, Instance, RecvMsg, RecvMsgBuf, ServiceAddr, Type, MAX_BATCH};
+
+const SERVICE_TYPE: Type = 18888;
+const SERVICE_INST: Instance = 42;
+
+/// The receiver gives up when no message arrived within the timeout,
+/// the messages may be dropped when the receive queue overflows.
+const RECV_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, StructOpt)]
+#[structopt(
+    name = "batch_bench",
+    about = "TIPC batched send and receive benchmark."
+)]
+struct Opt {
+    /// number of messages
+    #[structopt(short = "n", long, default_value = "100000")]
+    count: usize,
+
+    /// message size
+    #[structopt(short = "l", long = "msg-size", default_value = "64")]
+    msg_size: usize,
+
+    /// messages per batch, at most 64
+    #[structopt(short, long, default_value = "32")]
+    batch: usize,
+}
+
+/// Receives the messages until all of them or no more arrived, returns the number of the messages received.
+fn receive(rdm: Bound<Datagram>, opt: Opt, batched: bool) -> Fallible<usize> {
+    let mut received = 0;
+
+    rdm.as_ref().set_read_timeout(Some(RECV_TIMEOUT))?;
+
+    let res = if batched {
+        let mut bufs = vec![vec![0; opt.msg_size]; opt.batch];
+        let mut msg_bufs = (0..opt.batch)
+            .map(|_| RecvMsgBuf::new())
+            .collect::<Vec<_>>();
+
+        loop {
+            if received >= opt.count {
+                break Ok(());
+            }
+
+            match rdm.recv_batch(&mut bufs, &mut msg_bufs) {
+                Ok(n) => {
+                    received += msg_bufs[..n]
+                        .iter()
+                        .filter(|msg_buf| {
+                            matches!(msg_buf.received(), Some((RecvMsg::Message { .. }, _)))
+                        })
+                        .count()
+                }
+                Err(err) => break Err(err),
+            }
+        }
+    } else {
+        let mut buf = vec![0; opt.msg_size];
+
+        loop {
+            if received >= opt.count {
+                break Ok(());
+            }
+
+            match rdm.recv_from(&mut buf[..]) {
+                Ok(_) => received += 1,
+                Err(err) => break Err(err),
+            }
+        }
+    };
+
+    match res {
+        Err(ref err)
+            if err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::TimedOut =>
+        {
+            Ok(received)
+        }
+        res => res.map(|_| received).map_err(Into::into),
+    }
+}
+
+/// Sends the messages.
+fn send(rdm: &Datagram, srv: ServiceAddr, opt: Opt, batched: bool) -> Fallible<()> {
+    let payload = vec![0xAA; opt.msg_size];
+
+    if batched {
+        let msgs = vec![(&payload[..], srv); opt.batch.min(MAX_BATCH)];
+        let mut remaining = opt.count;
+
+        while remaining > 0 {
+            remaining -= rdm.send_batch(&msgs[..remaining.min(msgs.len())])?;
+        }
+    } else {
+        for _ in 0..opt.count {
+            rdm.send_to(&payload, srv)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> Fallible<()> {
+    let opt = Opt::from_args();
+    let srv = ServiceAddr::new(SERVICE_TYPE, SERVICE_INST);
+
+    for &batched in &[false, true] {
+        let server = tipc::rdm()?.bind(srv)?;
+        let receiver = thread::spawn(move || receive(server, opt, batched));
+        let client = tipc::rdm()?;
+        let start = Instant::now();
+
+        send(&client, srv, opt, batched)?;
+
+        let received = receiver.join().expect("receiver")?;
+        let elapsed = start.elapsed();
+
+        println!(
+            "{}: {} of {} messages received in {:?}, {:.0} msg/s",
+            if batched { "batched" } else { "per-message" },
+            received,
+            opt.count,
+            elapsed,
+            received as f64 / elapsed.as_secs_f64()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+//! A minimal `tipc bearer` showing how bearers are managed over the generic netlink interface.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use failure::Fallible;
+use structopt::StructOpt;
+
+use tipc::netlink::{self, Bearer, Udp, DEFAULT_UDP_PORT};
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "bearer", about = "TIPC bearer management demo.")]
+enum Opt {
+    /// List the enabled bearers
+    List,
+    /// Show the bearer properties
+    Get {
+        /// bearer name, such as eth:eth0
+        name: String,
+    },
+    /// Enable a bearer
+    Enable {
+        /// bearer name, such as eth:eth0
+        name: String,
+
+        /// discovery domain
+        #[structopt(short, long)]
+        domain: Option<u32>,
+
+        /// link priority
+        #[structopt(short, long)]
+        priority: Option<u32>,
+
+        /// link tolerance in milliseconds
+        #[structopt(short, long)]
+        tolerance: Option<u64>,
+
+        /// link window
+        #[structopt(short, long)]
+        window: Option<u32>,
+
+        /// local address of the UDP bearer
+        #[structopt(long)]
+        local: Option<SocketAddr>,
+
+        /// remote multicast or unicast address of the UDP bearer
+        #[structopt(long)]
+        remote: Option<SocketAddr>,
+    },
+    /// Add a remote peer to the UDP bearer
+    AddRemote {
+        /// bearer name, such as udp:b1
+        name: String,
+
+        /// remote unicast address
+        remote: IpAddr,
+
+        /// remote UDP port
+        #[structopt(long, default_value = "6118")]
+        port: u16,
+    },
+    /// Disable a bearer
+    Disable {
+        /// bearer name, such as eth:eth0
+        name: String,
+    },
+}
+
+fn show(bearer: &Bearer) {
+    println!("{}", bearer.name);
+    if let Some(priority) = bearer.props.priority {
+        println!("  priority: {}", priority);
+    }
+    if let Some(tolerance) = bearer.props.tolerance {
+        println!("  tolerance: {}ms", tolerance.as_millis());
+    }
+    if let Some(window) = bearer.props.window {
+        println!("  window: {}", window);
+    }
+    if let Some(mtu) = bearer.props.mtu {
+        println!("  mtu: {}", mtu);
+    }
+    if let Some(udp) = bearer.udp {
+        println!("  local: {}", udp.local);
+        println!("  remote: {}", udp.remote);
+    }
+}
+
+fn main() -> Fallible<()> {
+    let opt = Opt::from_args();
+    let client = netlink::connect()?;
+
+    match opt {
+        Opt::List => {
+            for bearer in client.bearers()? {
+                show(&bearer);
+            }
+        }
+        Opt::Get { name } => show(&client.bearer(&name)?),
+        Opt::Enable {
+            name,
+            domain,
+            priority,
+            tolerance,
+            window,
+            local,
+            remote,
+        } => {
+            let (media, ifname) = name.split_at(name.find(':').unwrap_or_default());
+            let mut bearer = Bearer::new(media.parse()?, ifname.trim_start_matches(':'));
+
+            bearer.domain = domain.map(From::from);
+            bearer.props.priority = priority;
+            bearer.props.tolerance = tolerance.map(Duration::from_millis);
+            bearer.props.window = window;
+
+            if let Some(local) = local {
+                let remote = remote.unwrap_or_else(|| {
+                    SocketAddr::new(Ipv4Addr::new(228, 0, 0, 1).into(), DEFAULT_UDP_PORT)
+                });
+
+                bearer.udp = Some(Udp::new(local, remote));
+            }
+
+            client.enable_bearer(&bearer)?;
+
+            println!("Bearer {} enabled", bearer.name);
+        }
+        Opt::AddRemote { name, remote, port } => {
+            client.add_udp_remote(&name, (remote, port))?;
+
+            for remote in client.udp_remotes(&name)? {
+                println!("{}", remote);
+            }
+        }
+        Opt::Disable { name } => {
+            client.disable_bearer(&name)?;
+
+            println!("Bearer {} disabled", name);
+        }
+    }
+
+    Ok(())
+}
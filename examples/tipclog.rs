@@ -80,7 +80,7 @@ fn main() -> Fallible<()> {
     println!("TIPC network event logger started");
 
     for event in &topsrv {
-        log_event(event, own_node)?;
+        log_event(event.context("receive topology event")?, own_node)?;
     }
 
     Ok(())
@@ -63,6 +63,16 @@ impl NetworkAddr {
     }
 }
 
+impl fmt::Debug for NetworkAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NetworkAddr")
+            .field("zone", &self.zone())
+            .field("cluster", &self.cluster())
+            .field("node", &self.node())
+            .finish()
+    }
+}
+
 impl fmt::Display for NetworkAddr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "<{}.{}.{}>", self.zone(), self.cluster(), self.node())
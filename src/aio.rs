@@ -0,0 +1,350 @@
+//! Asynchronous TIPC sockets for the `tokio` runtime.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::Instant;
+
+use futures_core::Stream as AsyncStream;
+use tokio::{
+    io::{unix::AsyncFd, AsyncRead, AsyncWrite, ReadBuf},
+    time,
+};
+
+use crate::{
+    addr::{Scope, ServiceAddr, ServiceRange, SocketAddr},
+    ffi,
+    sock::{
+        self, addr_not_available, Connectable, Connected, Datagram, Listener, Recv, RecvMsg, Send,
+        Socket, Stream, ToServiceAddrs, ToSocketAddrs,
+    },
+    topo::{self, Event, Link, Links, Node, Nodes, Server, Subscription},
+};
+
+/// A TIPC socket registered with the `tokio` reactor.
+///
+/// The wrapped socket is moved into nonblocking mode,
+/// and each operation waits for the readiness of the socket before it is retried.
+#[derive(Debug)]
+pub struct Async<T: AsRawFd>(AsyncFd<T>);
+
+impl<T: AsRawFd> AsRawFd for Async<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl<T: AsRawFd> Async<T> {
+    /// Moves the socket into nonblocking mode and registers it with the current `tokio` reactor.
+    ///
+    /// This function panics if there is no current reactor set.
+    pub fn new(io: T) -> io::Result<Self> {
+        sock::set_nonblocking(io.as_raw_fd(), true)?;
+
+        AsyncFd::new(io).map(Async)
+    }
+
+    /// Gets a reference to the underlying socket.
+    pub fn get_ref(&self) -> &T {
+        self.0.get_ref()
+    }
+
+    /// Gets a mutable reference to the underlying socket.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut()
+    }
+
+    /// Deregisters the socket and returns it, it remains in nonblocking mode.
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+
+    /// Performs a read operation once the socket is readable.
+    pub async fn read_with<R, F>(&self, mut op: F) -> io::Result<R>
+    where
+        F: FnMut(&T) -> io::Result<R>,
+    {
+        loop {
+            let mut guard = self.0.readable().await?;
+
+            match guard.try_io(|inner| op(inner.get_ref())) {
+                Ok(res) => return res,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Performs a write operation once the socket is writable.
+    pub async fn write_with<R, F>(&self, mut op: F) -> io::Result<R>
+    where
+        F: FnMut(&T) -> io::Result<R>,
+    {
+        loop {
+            let mut guard = self.0.writable().await?;
+
+            match guard.try_io(|inner| op(inner.get_ref())) {
+                Ok(res) => return res,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    fn poll_read_with<R, F>(&self, cx: &mut Context, mut op: F) -> Poll<io::Result<R>>
+    where
+        F: FnMut(&T) -> io::Result<R>,
+    {
+        loop {
+            let mut guard = ready!(self.0.poll_read_ready(cx))?;
+
+            match guard.try_io(|inner| op(inner.get_ref())) {
+                Ok(res) => return Poll::Ready(res),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    fn poll_write_with<R, F>(&self, cx: &mut Context, mut op: F) -> Poll<io::Result<R>>
+    where
+        F: FnMut(&T) -> io::Result<R>,
+    {
+        loop {
+            let mut guard = ready!(self.0.poll_write_ready(cx))?;
+
+            match guard.try_io(|inner| op(inner.get_ref())) {
+                Ok(res) => return Poll::Ready(res),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl<T> Async<T>
+where
+    T: AsRawFd + AsRef<Socket>,
+{
+    /// Receives a message, a returned undelivered message or a group membership event.
+    pub async fn recv_msg(&self, buf: &mut [u8], flags: Recv) -> io::Result<(RecvMsg, SocketAddr)> {
+        self.read_with(|sock| sock.as_ref().recv_msg(&mut *buf, flags))
+            .await
+    }
+}
+
+impl<T> Async<Listener<T>>
+where
+    T: Connectable + AsRawFd,
+{
+    /// Accept a new incoming connection from this listener.
+    ///
+    /// The accepted connection is registered with the same reactor.
+    pub async fn accept(&self) -> io::Result<(Async<Connected<T>>, SocketAddr)> {
+        let (conn, addr) = self.read_with(|listener| listener.accept()).await?;
+
+        Ok((Async::new(conn)?, addr))
+    }
+}
+
+impl<T> Async<Connected<T>>
+where
+    T: AsRef<Socket> + AsRawFd,
+{
+    /// Receives data from the socket.
+    ///
+    /// On success, returns the number of bytes read.
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_with(|conn| conn.recv(&mut *buf)).await
+    }
+
+    /// Sends data on the socket to the remote address to which it is connected.
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.write_with(|conn| conn.send(buf)).await
+    }
+}
+
+impl Async<Datagram> {
+    /// Receives data from the socket.
+    ///
+    /// On success, returns the number of bytes read and the address from whence the data came.
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.read_with(|sock| sock.recv_from(&mut *buf)).await
+    }
+
+    /// Like `recv_from`, except that it also returns the service range the message was sent to.
+    pub async fn recv_from_vectored(
+        &self,
+        bufs: &mut [io::IoSliceMut<'_>],
+    ) -> io::Result<(usize, SocketAddr, Option<ServiceRange>)> {
+        self.read_with(|sock| sock.recv_from_vectored(&mut *bufs))
+            .await
+    }
+
+    /// Sends data on the socket to the given address. On success, returns the number of bytes written.
+    pub async fn send_to<A: ToSocketAddrs>(&self, buf: &[u8], dst: A) -> io::Result<usize> {
+        let dst: ffi::sockaddr_tipc = dst
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(addr_not_available)?
+            .into();
+
+        self.write_with(|sock| sock.as_ref().send_to(buf, dst, Send::empty()))
+            .await
+    }
+}
+
+impl AsyncRead for Async<Connected<Stream>> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf,
+    ) -> Poll<io::Result<()>> {
+        let unfilled = buf.initialize_unfilled();
+        let len = ready!(self.poll_read_with(cx, |conn| conn.recv(&mut *unfilled)))?;
+
+        buf.advance(len);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for Async<Connected<Stream>> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.poll_write_with(cx, |conn| conn.send(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(self.get_ref().shutdown())
+    }
+}
+
+impl Async<Server> {
+    /// The subscriber wants `All` or `Edge` event for each matching update of the binding table.
+    pub async fn subscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<Subscription> {
+        let sub = sub.into();
+
+        self.write_with(|srv| srv.subscribe(sub)).await
+    }
+
+    /// The subscriber doesn't want any more events for this service range.
+    pub async fn unsubscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<()> {
+        let sub = sub.into();
+
+        self.write_with(|srv| srv.unsubscribe(sub)).await
+    }
+
+    /// Receives events for the subscribed service ranges.
+    pub async fn recv(&self) -> io::Result<Event> {
+        self.read_with(|srv| srv.recv()).await
+    }
+}
+
+/// A stream of the topology events.
+///
+/// An expired subscription is yielded as an `io::ErrorKind::TimedOut` error wrapping `topo::Expired`,
+/// and the stream ends when the topology server closes the connection.
+impl AsyncStream for Async<Server> {
+    type Item = io::Result<Event>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.poll_read_with(cx, |srv| srv.recv_event())
+            .map(Result::transpose)
+    }
+}
+
+/// A stream of the node events.
+impl AsyncStream for Async<Nodes> {
+    type Item = io::Result<Node>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.poll_read_with(cx, |nodes| nodes.recv_event())
+            .map(Result::transpose)
+    }
+}
+
+/// A stream of the link events.
+impl AsyncStream for Async<Links> {
+    type Item = io::Result<Link>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.poll_read_with(cx, |links| links.recv_event())
+            .map(Result::transpose)
+    }
+}
+
+/// Waits the service ready.
+///
+/// Returns `false` if the deadline elapsed before the service was published.
+///
+/// The subscription is cancelled by the topology server once its connection is closed,
+/// which happens when the returned future completes or is dropped.
+pub async fn wait<A: ToServiceAddrs>(addr: A, deadline: Option<Instant>) -> io::Result<bool> {
+    let (addr, scope) = addr
+        .to_service_addrs()?
+        .next()
+        .ok_or_else(addr_not_available)?;
+
+    with_deadline(deadline, wait_published(&[addr], scope)).await
+}
+
+/// Waits all the services ready.
+///
+/// Returns `false` if the deadline elapsed before all the services were published at the same time.
+///
+/// The subscriptions are cancelled by the topology server once its connection is closed,
+/// which happens when the returned future completes or is dropped.
+pub async fn wait_all(addrs: &[ServiceAddr], deadline: Option<Instant>) -> io::Result<bool> {
+    with_deadline(deadline, wait_published(addrs, Scope::Global)).await
+}
+
+async fn with_deadline<F>(deadline: Option<Instant>, fut: F) -> io::Result<bool>
+where
+    F: Future<Output = io::Result<bool>>,
+{
+    match deadline {
+        Some(deadline) => time::timeout_at(deadline.into(), fut)
+            .await
+            .unwrap_or(Ok(false)),
+        None => fut.await,
+    }
+}
+
+async fn wait_published(services: &[ServiceAddr], scope: Scope) -> io::Result<bool> {
+    let srv = Async::new(topo::connect(scope)?)?;
+
+    for (idx, &service) in services.iter().enumerate() {
+        srv.subscribe(Subscription::from(service).edge().userdata(idx as u64))
+            .await?;
+    }
+
+    let mut published = vec![false; services.len()];
+    let mut pending = services.len();
+
+    while pending > 0 {
+        let evt = srv.recv().await?;
+
+        if let Scope::Node(node) = scope {
+            if node.get() != evt.sock().node() {
+                continue;
+            }
+        }
+
+        if let Some(published) = published.get_mut(evt.userdata as usize) {
+            if *published != evt.available() {
+                *published = evt.available();
+
+                if *published {
+                    pending -= 1;
+                } else {
+                    pending += 1;
+                }
+            }
+        }
+    }
+
+    Ok(true)
+}
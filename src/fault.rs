@@ -0,0 +1,160 @@
+//! Fault injection for the test clusters.
+//!
+//! The faults are injected into a simulated `mock::Fabric`, whose nodes are identified by their addresses,
+//! or into a `netns::Cluster`, whose nodes are identified by their indexes.
+//!
+//! Note that the TIPC links of a real cluster retransmit the lost packets,
+//! so the loss only delays the messages, until the link tolerance is exceeded and the link is reset.
+
+use core::time::Duration;
+
+use std::io;
+use std::thread;
+use std::time::Instant;
+
+/// The impairment of the traffic from a node to its peer.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Impairment {
+    /// The percentage of the dropped messages.
+    pub loss: f64,
+    /// The delay of the messages.
+    pub delay: Duration,
+    /// The percentage of the messages sent without the delay, ahead of the delayed ones.
+    pub reorder: f64,
+}
+
+impl Impairment {
+    pub fn loss(mut self, percent: f64) -> Self {
+        self.loss = percent;
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn reorder(mut self, percent: f64) -> Self {
+        self.reorder = percent;
+        self
+    }
+
+    /// The traffic is not impaired.
+    pub fn is_empty(&self) -> bool {
+        *self == Impairment::default()
+    }
+
+    /// Checks the percentages, the reordering requires a delay.
+    pub fn validate(&self) -> io::Result<()> {
+        for &(name, percent) in &[("loss", self.loss), ("reorder", self.reorder)] {
+            if !(0.0..=100.0).contains(&percent) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} {}% out of range 0..=100", name, percent),
+                ));
+            }
+        }
+
+        if self.reorder > 0.0 && self.delay == Duration::default() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("reorder {}% without delay", self.reorder),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// A fault injected into the cluster.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fault<N> {
+    /// Impairs the traffic from the node to the peer, an empty impairment heals it.
+    Impair {
+        node: N,
+        peer: N,
+        impairment: Impairment,
+    },
+    /// Takes down the bearer of the node towards the peer, the link between them goes down.
+    BearerDown { node: N, peer: N },
+    /// Brings up the bearer of the node towards the peer.
+    BearerUp { node: N, peer: N },
+    /// Kills the node, its peers see it down.
+    Kill(N),
+}
+
+/// A cluster which the faults can be injected into.
+pub trait Inject {
+    /// The identity of the nodes.
+    type Node: Copy;
+
+    /// Injects the fault.
+    fn inject(&self, fault: Fault<Self::Node>) -> io::Result<()>;
+}
+
+/// A script of the faults injected at their offsets from the start.
+#[derive(Clone, Debug)]
+pub struct Script<N> {
+    steps: Vec<(Duration, Fault<N>)>,
+}
+
+impl<N: Copy> Default for Script<N> {
+    fn default() -> Self {
+        Script::new()
+    }
+}
+
+impl<N: Copy> Script<N> {
+    /// Creates an empty script.
+    pub fn new() -> Self {
+        Script { steps: vec![] }
+    }
+
+    /// Injects the fault at the offset from the start.
+    pub fn at(mut self, offset: Duration, fault: Fault<N>) -> Self {
+        let idx = self.steps.partition_point(|&(at, _)| at <= offset);
+
+        self.steps.insert(idx, (offset, fault));
+        self
+    }
+
+    /// The faults with their offsets, in the order of injection.
+    pub fn steps(&self) -> &[(Duration, Fault<N>)] {
+        &self.steps
+    }
+
+    /// Runs the script against the cluster, blocking until the last fault is injected.
+    pub fn run<T: Inject<Node = N>>(&self, target: &T) -> io::Result<()> {
+        let start = Instant::now();
+
+        for &(offset, fault) in &self.steps {
+            if let Some(delay) = (start + offset).checked_duration_since(Instant::now()) {
+                thread::sleep(delay);
+            }
+
+            target.inject(fault)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        assert!(Impairment::default().validate().is_ok());
+        assert!(Impairment::default()
+            .loss(10.0)
+            .delay(Duration::from_millis(10))
+            .reorder(25.0)
+            .validate()
+            .is_ok());
+
+        assert!(Impairment::default().loss(101.0).validate().is_err());
+        assert!(Impairment::default().reorder(-1.0).validate().is_err());
+        assert!(Impairment::default().reorder(25.0).validate().is_err());
+    }
+}
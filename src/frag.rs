@@ -0,0 +1,952 @@
+//! A fragmentation layer for the messages larger than `TIPC_MAX_USER_MSG_SIZE`.
+//!
+//! A message is split into the fragments, each sent as a single TIPC message with a header
+//! of the message identity, the fragment index and the number of fragments, all in network byte order,
+//! and the fragments are reassembled at the receiver, keeping the message boundaries.
+//!
+//! TIPC delivers the messages between two sockets in order, so a fragment out of sequence
+//! means that the previous ones were lost, and the partial message is discarded,
+//! as well as a partial message interrupted by a new message from the same socket.
+//! The partial messages are also discarded when they are not completed in time, which fails the receive.
+//! The waits for the remaining fragments are bounded by the read timeout of the socket,
+//! which is managed by this layer.
+//!
+//! A fragment rejected by an overloaded receiver is returned to a rejectable sender,
+//! which sends the whole message again until the retries are exhausted.
+//! The senders must be rejectable to recover from the rejected fragments, see `Datagram::set_rejectable`.
+
+use core::fmt;
+use core::time::Duration;
+
+use std::collections::{HashMap, VecDeque};
+use std::error;
+use std::io;
+use std::time::Instant;
+
+use crate::{
+    addr::SocketAddr,
+    ffi,
+    sock::{addr_not_available, ErrorCode, Recv, RecvMsg, ToSocketAddrs},
+    transport::{MessageSocket, SeqPacketSocket},
+};
+
+/// The length of the fragment header.
+pub const HEADER_LEN: usize = 12;
+
+/// The maximum length of the data carried by a fragment.
+pub const MAX_FRAGMENT_LEN: usize = ffi::TIPC_MAX_USER_MSG_SIZE as usize - HEADER_LEN;
+
+/// The partial messages are discarded if not completed within the timeout.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The messages are sent again at most the times if a fragment is rejected by an overloaded receiver.
+pub const DEFAULT_RETRIES: usize = 3;
+
+/// The messages longer than the length are discarded by the receiver.
+pub const DEFAULT_MAX_LEN: usize = 64 * 1024 * 1024;
+
+/// The total length of the messages kept to be sent again.
+pub const DEFAULT_MAX_SENT: usize = 16 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Header {
+    id: u32,
+    index: u32,
+    count: u32,
+}
+
+impl Header {
+    fn parse(buf: &[u8]) -> io::Result<(Header, &[u8])> {
+        if buf.len() < HEADER_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("fragment too short, {} bytes", buf.len()),
+            ));
+        }
+
+        let field = |i: usize| u32::from_be_bytes([buf[i], buf[i + 1], buf[i + 2], buf[i + 3]]);
+        let hdr = Header {
+            id: field(0),
+            index: field(4),
+            count: field(8),
+        };
+
+        if hdr.index >= hdr.count {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("fragment {} of {}", hdr.index, hdr.count),
+            ))
+        } else {
+            Ok((hdr, &buf[HEADER_LEN..]))
+        }
+    }
+
+    /// Checks the number of fragments of a message, which is read from the wire.
+    fn check_len(&self, max_len: usize) -> io::Result<()> {
+        if self.count as usize > max_len.div_ceil(MAX_FRAGMENT_LEN).max(1) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("message {} of {} fragments too large", self.id, self.count),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) {
+        buf.clear();
+        buf.extend_from_slice(&self.id.to_be_bytes());
+        buf.extend_from_slice(&self.index.to_be_bytes());
+        buf.extend_from_slice(&self.count.to_be_bytes());
+    }
+}
+
+/// Sends the message in the fragments.
+fn send_fragments<F>(id: u32, data: &[u8], mut send: F) -> io::Result<()>
+where
+    F: FnMut(&[u8]) -> io::Result<usize>,
+{
+    let count = data.len().div_ceil(MAX_FRAGMENT_LEN).max(1);
+
+    if count > u32::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "message too large",
+        ));
+    }
+
+    let mut buf = Vec::with_capacity(HEADER_LEN + data.len().min(MAX_FRAGMENT_LEN));
+    let mut fragment = |index: usize, chunk: &[u8]| {
+        Header {
+            id,
+            index: index as u32,
+            count: count as u32,
+        }
+        .write(&mut buf);
+        buf.extend_from_slice(chunk);
+
+        send(&buf).map(|_| ())
+    };
+
+    // an empty message is sent as a single empty fragment
+    if data.is_empty() {
+        return fragment(0, data);
+    }
+
+    data.chunks(MAX_FRAGMENT_LEN)
+        .enumerate()
+        .try_for_each(|(index, chunk)| fragment(index, chunk))
+}
+
+/// Bounds the next wait by the deadline, the read timeout is only cleared if it was set.
+fn set_deadline<F>(
+    timed: &mut bool,
+    deadline: Option<Instant>,
+    now: Instant,
+    set_read_timeout: F,
+) -> io::Result<()>
+where
+    F: FnOnce(Option<Duration>) -> io::Result<()>,
+{
+    if deadline.is_some() || *timed {
+        set_read_timeout(deadline.map(|deadline| deadline - now))?;
+
+        *timed = deadline.is_some();
+    }
+
+    Ok(())
+}
+
+fn timed_out(id: u32) -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, format!("message {} timed out", id))
+}
+
+/// A message partially received.
+#[derive(Debug)]
+struct Partial {
+    next: u32,
+    data: Vec<u8>,
+    deadline: Instant,
+}
+
+impl Partial {
+    /// Appends the fragment, returns the message if it is completed.
+    ///
+    /// A fragment out of sequence discards the partial message, or starts a new one if it is the first one.
+    fn append(
+        partial: Option<Partial>,
+        hdr: Header,
+        data: &[u8],
+        timeout: Duration,
+    ) -> Result<Vec<u8>, Option<Partial>> {
+        let mut partial = match partial {
+            Some(partial) if partial.next == hdr.index => partial,
+            _ if hdr.index == 0 => Partial {
+                next: 0,
+                data: vec![],
+                deadline: Instant::now() + timeout,
+            },
+            _ => return Err(None),
+        };
+
+        partial.data.extend_from_slice(data);
+        partial.next += 1;
+
+        if partial.next == hdr.count {
+            Ok(partial.data)
+        } else {
+            Err(Some(partial))
+        }
+    }
+}
+
+/// A message sent, kept to be sent again if rejected.
+struct Sent {
+    /// The identity returned when the message was sent the first time.
+    id: u32,
+    dst: ffi::sockaddr_tipc,
+    /// The data to be sent again, if there are retries left and it was not forgotten.
+    data: Option<Vec<u8>>,
+    retries: usize,
+    deadline: Instant,
+    /// The message was sent again or reported undelivered, its other rejected fragments are ignored.
+    handled: bool,
+}
+
+impl fmt::Debug for Sent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Sent")
+            .field("id", &self.id)
+            .field("len", &self.data.as_ref().map(|data| data.len()))
+            .field("retries", &self.retries)
+            .field("deadline", &self.deadline)
+            .field("handled", &self.handled)
+            .finish()
+    }
+}
+
+/// A fragmented message couldn't be delivered.
+///
+/// It is reported as the inner error of the `io::Error` converted from the error code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Undelivered {
+    /// The identity of the message, returned when it was sent.
+    pub id: u32,
+    /// The reason why a fragment of the message was rejected.
+    pub err: ErrorCode,
+}
+
+impl fmt::Display for Undelivered {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "message {} undelivered, {}", self.id, self.err)
+    }
+}
+
+impl error::Error for Undelivered {}
+
+impl From<Undelivered> for io::Error {
+    fn from(undelivered: Undelivered) -> Self {
+        io::Error::new(io::Error::from(undelivered.err).kind(), undelivered)
+    }
+}
+
+/// The large messages over a connectionless socket.
+#[derive(Debug)]
+pub struct Datagrams<S> {
+    sock: S,
+    timeout: Duration,
+    retries: usize,
+    max_len: usize,
+    max_sent: usize,
+    next_id: u32,
+    partials: HashMap<SocketAddr, (u32, Partial)>,
+    /// The messages sent, by the identity of their last sending.
+    sent: HashMap<u32, Sent>,
+    /// The messages sent, in the order of their deadlines.
+    sent_order: VecDeque<u32>,
+    /// The messages kept to be sent again, in the order of their sending.
+    kept: VecDeque<u32>,
+    kept_len: usize,
+    buf: Vec<u8>,
+    timed: bool,
+}
+
+impl<S: MessageSocket> Datagrams<S> {
+    /// Sends and receives the large messages on the socket.
+    pub fn new(sock: S) -> Self {
+        Datagrams {
+            sock,
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            max_len: DEFAULT_MAX_LEN,
+            max_sent: DEFAULT_MAX_SENT,
+            next_id: 0,
+            partials: HashMap::new(),
+            sent: HashMap::new(),
+            sent_order: VecDeque::new(),
+            kept: VecDeque::new(),
+            kept_len: 0,
+            buf: vec![0; ffi::TIPC_MAX_USER_MSG_SIZE as usize],
+            timed: false,
+        }
+    }
+
+    /// Fails the partial messages not completed within the timeout, and forgets the messages sent after it.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends a message again at most the times if it is rejected by an overloaded receiver.
+    ///
+    /// The messages are kept until the timeout to be sent again, unless the retries are zero.
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Discards the received messages longer than the length, rounded up to whole fragments.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Keeps the messages to be sent again up to the total length,
+    /// the oldest ones beyond it are not sent again.
+    pub fn max_sent(mut self, max_sent: usize) -> Self {
+        self.max_sent = max_sent;
+        self
+    }
+
+    /// The underlying socket.
+    pub fn get_ref(&self) -> &S {
+        &self.sock
+    }
+
+    /// Unwraps the underlying socket, the partial messages are discarded.
+    pub fn into_inner(self) -> S {
+        self.sock
+    }
+
+    /// The number of the partial messages.
+    pub fn pending(&self) -> usize {
+        self.partials.len()
+    }
+
+    /// Sends the message to the destination, a socket address or a service address.
+    ///
+    /// On success, returns the identity of the message.
+    pub fn send_to<A: ToSocketAddrs>(&mut self, buf: &[u8], dst: A) -> io::Result<u32> {
+        let dst = dst
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(addr_not_available)?
+            .into();
+        let id = self.next_id;
+
+        self.send(id, buf.to_vec(), dst, self.retries)?;
+
+        Ok(id)
+    }
+
+    /// Sends the message `id` with a new identity, so that the fragments rejected
+    /// before it was sent again can be told apart.
+    fn send(
+        &mut self,
+        id: u32,
+        data: Vec<u8>,
+        dst: ffi::sockaddr_tipc,
+        retries: usize,
+    ) -> io::Result<()> {
+        let now = Instant::now();
+        let sent_id = self.next_id;
+        let sock = &self.sock;
+
+        self.next_id = self.next_id.wrapping_add(1);
+
+        send_fragments(sent_id, &data, |buf| sock.send_to(buf, dst))?;
+
+        let data = if retries > 0 {
+            self.kept.push_back(sent_id);
+            self.kept_len += data.len();
+
+            Some(data)
+        } else {
+            None
+        };
+
+        self.sent.insert(
+            sent_id,
+            Sent {
+                id,
+                dst,
+                data,
+                retries,
+                deadline: now + self.timeout,
+                handled: false,
+            },
+        );
+        self.sent_order.push_back(sent_id);
+        self.prune(now);
+
+        Ok(())
+    }
+
+    /// Forgets the messages sent before the timeout, and the data of the oldest ones beyond `max_sent`.
+    fn prune(&mut self, now: Instant) {
+        while let Some(&sent_id) = self.sent_order.front() {
+            match self.sent.get(&sent_id) {
+                Some(sent) if sent.deadline > now => break,
+                _ => {
+                    self.sent_order.pop_front();
+
+                    if let Some(data) = self.sent.remove(&sent_id).and_then(|sent| sent.data) {
+                        self.kept_len -= data.len();
+                    }
+                }
+            }
+        }
+
+        while self.kept_len > self.max_sent {
+            let sent_id = match self.kept.pop_front() {
+                Some(sent_id) => sent_id,
+                None => break,
+            };
+
+            if let Some(data) = self
+                .sent
+                .get_mut(&sent_id)
+                .and_then(|sent| sent.data.take())
+            {
+                self.kept_len -= data.len();
+            }
+        }
+
+        // the messages forgotten, or which data was already taken
+        while let Some(sent_id) = self.kept.front() {
+            match self.sent.get(sent_id) {
+                Some(Sent { data: Some(_), .. }) => break,
+                _ => {
+                    self.kept.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Receives a message, and the address from whence it came.
+    ///
+    /// A partial message not completed within the timeout is discarded, and reported as a `TimedOut` error.
+    /// A message sent by this socket which couldn't be delivered
+    /// is reported as an error with `Undelivered`, unless it is sent again.
+    pub fn recv_from(&mut self) -> io::Result<(Vec<u8>, SocketAddr)> {
+        loop {
+            let now = Instant::now();
+
+            self.prune(now);
+
+            if let Some((&from, &(id, _))) = self
+                .partials
+                .iter()
+                .find(|(_, (_, partial))| partial.deadline <= now)
+            {
+                self.partials.remove(&from);
+
+                return Err(timed_out(id));
+            }
+
+            let deadline = self
+                .partials
+                .values()
+                .map(|(_, partial)| partial.deadline)
+                .min();
+            let sock = &self.sock;
+
+            set_deadline(&mut self.timed, deadline, now, |timeout| {
+                sock.set_read_timeout(timeout)
+            })?;
+
+            let res = match self.sock.recv_msg(&mut self.buf[..], Recv::empty()) {
+                Err(ref err)
+                    if err.kind() == io::ErrorKind::WouldBlock
+                        && deadline.is_some_and(|deadline| deadline <= Instant::now()) =>
+                {
+                    continue
+                }
+                res => res?,
+            };
+
+            match res {
+                (RecvMsg::Message { len, .. }, from) => {
+                    let (hdr, data) = Header::parse(&self.buf[..len])?;
+
+                    // A new message from the socket supersedes its partial message.
+                    let partial = match self.partials.remove(&from) {
+                        Some((id, partial)) if id == hdr.id => Some(partial),
+                        _ => None,
+                    };
+
+                    if partial.is_none() && hdr.index == 0 {
+                        hdr.check_len(self.max_len)?;
+                    }
+
+                    match Partial::append(partial, hdr, data, self.timeout) {
+                        Ok(data) => return Ok((data, from)),
+                        Err(Some(partial)) => {
+                            self.partials.insert(from, (hdr.id, partial));
+                        }
+                        Err(None) => {}
+                    }
+                }
+                (RecvMsg::Rejected(rejected), _) => {
+                    let len = rejected.len.min(self.buf.len());
+                    let (hdr, _) = Header::parse(&self.buf[..len])?;
+                    let err = rejected.err;
+
+                    let sent = match self.sent.get_mut(&hdr.id) {
+                        // The other fragments of a message sent again or reported are ignored.
+                        Some(sent) if sent.handled => continue,
+                        Some(sent) => sent,
+                        // The message was forgotten after the timeout.
+                        None => return Err(Undelivered { id: hdr.id, err }.into()),
+                    };
+
+                    sent.handled = true;
+
+                    let (id, dst, retries) = (sent.id, sent.dst, sent.retries);
+
+                    match sent.data.take() {
+                        Some(data) if err == ErrorCode::Overload && retries > 0 => {
+                            self.kept_len -= data.len();
+                            self.send(id, data, dst, retries - 1)?;
+                        }
+                        data => {
+                            self.kept_len -= data.map_or(0, |data| data.len());
+
+                            return Err(Undelivered { id, err }.into());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The large messages over a connection.
+#[derive(Debug)]
+pub struct Connection<S> {
+    sock: S,
+    timeout: Duration,
+    max_len: usize,
+    next_id: u32,
+    partial: Option<(u32, Partial)>,
+    buf: Vec<u8>,
+    /// The length of the fragment left in the buffer, which starts the next message.
+    unread: Option<usize>,
+    timed: bool,
+}
+
+impl<S: SeqPacketSocket> Connection<S> {
+    /// Sends and receives the large messages on the connection.
+    pub fn new(sock: S) -> Self {
+        Connection {
+            sock,
+            timeout: DEFAULT_TIMEOUT,
+            max_len: DEFAULT_MAX_LEN,
+            next_id: 0,
+            partial: None,
+            buf: vec![0; ffi::TIPC_MAX_USER_MSG_SIZE as usize],
+            unread: None,
+            timed: false,
+        }
+    }
+
+    /// Fails a partial message if not completed within the timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Fails the received messages longer than the length, rounded up to whole fragments.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// The underlying connection.
+    pub fn get_ref(&self) -> &S {
+        &self.sock
+    }
+
+    /// Unwraps the underlying connection, the partial message is discarded.
+    pub fn into_inner(self) -> S {
+        self.sock
+    }
+
+    /// Sends the message through the connection.
+    ///
+    /// On success, returns the identity of the message.
+    pub fn send(&mut self, buf: &[u8]) -> io::Result<u32> {
+        let id = self.next_id;
+        let sock = &self.sock;
+
+        self.next_id = self.next_id.wrapping_add(1);
+
+        send_fragments(id, buf, |buf| sock.send(buf)).map(|_: ()| id)
+    }
+
+    /// Receives a message, or `None` if the peer has shut down the connection.
+    ///
+    /// A fragment out of sequence, a partial message interrupted by a new message,
+    /// a partial message not completed within the timeout, or a message too large,
+    /// fails the receive, and the partial message is discarded. The remaining fragments
+    /// of a discarded message are skipped, until the first fragment of the next message.
+    pub fn recv(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            let len = match self.unread.take() {
+                Some(len) => len,
+                None => match self.recv_fragment()? {
+                    Some(len) => len,
+                    None => continue,
+                },
+            };
+
+            if len == 0 {
+                return if self.partial.take().is_some() {
+                    Err(io::ErrorKind::UnexpectedEof.into())
+                } else {
+                    Ok(None)
+                };
+            }
+
+            let (hdr, data) = Header::parse(&self.buf[..len])?;
+            let partial = match self.partial.take() {
+                Some((id, partial)) if id == hdr.id => Some(partial),
+                Some((id, _)) => {
+                    // the new message is assembled by the next receive
+                    if hdr.index == 0 {
+                        self.unread = Some(len);
+                    }
+
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("message {} interrupted by message {}", id, hdr.id),
+                    ));
+                }
+                // the rest of a discarded message
+                None if hdr.index != 0 => continue,
+                None => {
+                    hdr.check_len(self.max_len)?;
+
+                    None
+                }
+            };
+
+            match Partial::append(partial, hdr, data, self.timeout) {
+                Ok(data) => return Ok(Some(data)),
+                Err(Some(partial)) => self.partial = Some((hdr.id, partial)),
+                Err(None) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "fragment {} of message {} out of sequence",
+                            hdr.index, hdr.id
+                        ),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Receives a fragment into the buffer, or `None` if the wait should be bounded again.
+    fn recv_fragment(&mut self) -> io::Result<Option<usize>> {
+        let now = Instant::now();
+        let deadline = self.partial.as_ref().map(|(_, partial)| partial.deadline);
+
+        if deadline.is_some_and(|deadline| deadline <= now) {
+            let (id, _) = self.partial.take().unwrap();
+
+            return Err(timed_out(id));
+        }
+
+        let sock = &self.sock;
+
+        set_deadline(&mut self.timed, deadline, now, |timeout| {
+            sock.set_read_timeout(timeout)
+        })?;
+
+        match self.sock.recv(&mut self.buf[..]) {
+            Ok(len) => Ok(Some(len)),
+            Err(ref err)
+                if err.kind() == io::ErrorKind::WouldBlock
+                    && deadline.is_some_and(|deadline| deadline <= Instant::now()) =>
+            {
+                Ok(None)
+            }
+            Err(err) => {
+                self.partial = None;
+
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use std::thread;
+
+    use crate::{addr::ServiceAddr, mock::Fabric};
+
+    use super::*;
+
+    const SERVICE: ServiceAddr = ServiceAddr::new(18888, 17);
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn datagrams() {
+        let fabric = Fabric::new();
+        let server = fabric.node(1).unwrap().rdm().unwrap();
+        let client = fabric.node(2).unwrap().rdm().unwrap();
+        let other = fabric.node(2).unwrap().rdm().unwrap();
+        let addr = client.local_addr().unwrap();
+
+        server.bind(SERVICE).unwrap();
+
+        let mut server = Datagrams::new(server).timeout(Duration::from_millis(10));
+        let mut client = Datagrams::new(client);
+
+        client.send_to(&message(200_000), SERVICE).unwrap();
+        client.send_to(&[], SERVICE).unwrap();
+
+        assert_eq!(server.recv_from().unwrap(), (message(200_000), addr));
+        assert_eq!(server.recv_from().unwrap(), (vec![], addr));
+
+        // the first fragment of a message which is never completed
+        let mut buf = vec![];
+
+        Header {
+            id: 42,
+            index: 0,
+            count: 2,
+        }
+        .write(&mut buf);
+        other.send_to(&buf, SERVICE).unwrap();
+        client.send_to(b"hello", SERVICE).unwrap();
+
+        assert_eq!(server.recv_from().unwrap(), (b"hello".to_vec(), addr));
+        assert_eq!(server.pending(), 1);
+
+        // the wait is bounded by the timeout of the partial message
+        assert_eq!(
+            server.recv_from().unwrap_err().kind(),
+            io::ErrorKind::TimedOut
+        );
+        assert_eq!(server.pending(), 0);
+
+        // a new message from the socket supersedes its partial message
+        client.get_ref().send_to(&buf, SERVICE).unwrap();
+        client.send_to(b"world", SERVICE).unwrap();
+
+        assert_eq!(server.recv_from().unwrap(), (b"world".to_vec(), addr));
+        assert_eq!(server.pending(), 0);
+
+        // a message too large is discarded
+        let mut server = server.max_len(100_000);
+
+        client.send_to(&message(200_000), SERVICE).unwrap();
+        client.send_to(b"hello", SERVICE).unwrap();
+
+        assert_eq!(
+            server.recv_from().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(server.recv_from().unwrap(), (b"hello".to_vec(), addr));
+    }
+
+    #[test]
+    fn rejected() {
+        let fabric = Fabric::new();
+        let server = fabric.node(1).unwrap().rdm().unwrap();
+        let client = fabric.node(2).unwrap().rdm().unwrap();
+        let from = client.local_addr().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        client.set_rejectable(true).unwrap();
+        client.set_nonblocking(true).unwrap();
+        server.set_recv_buf_size(100_000).unwrap();
+
+        let mut client = Datagrams::new(client).retries(1);
+        let mut server = Datagrams::new(server);
+
+        // the second fragment overloads the receiver, and the message is sent again
+        client.send_to(&message(100_000), addr).unwrap();
+        server.get_ref().set_recv_buf_size(1_000_000).unwrap();
+
+        assert_eq!(
+            client.recv_from().unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+        assert_eq!(server.recv_from().unwrap(), (message(100_000), from));
+        assert_eq!(server.pending(), 0);
+
+        // the message sent again is rejected too, and reported with its identity
+        server.get_ref().set_recv_buf_size(100_000).unwrap();
+
+        let id = client.send_to(&message(100_000), addr).unwrap();
+        let err = client.recv_from().unwrap_err();
+
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<Undelivered>(),
+            Some(&Undelivered {
+                id,
+                err: ErrorCode::Overload
+            })
+        );
+        assert_eq!(
+            client.recv_from().unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        drop(server);
+
+        let id = client.send_to(b"hello", addr).unwrap();
+        let err = client.recv_from().unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<Undelivered>(),
+            Some(&Undelivered {
+                id,
+                err: ErrorCode::NoPort
+            })
+        );
+    }
+
+    #[test]
+    fn forgotten() {
+        let fabric = Fabric::new();
+        let server = fabric.node(1).unwrap().rdm().unwrap();
+        let client = fabric.node(2).unwrap().rdm().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        client.set_rejectable(true).unwrap();
+        client.set_nonblocking(true).unwrap();
+        server.set_recv_buf_size(100_000).unwrap();
+
+        let mut client = Datagrams::new(client)
+            .timeout(Duration::from_millis(10))
+            .max_sent(150_000);
+
+        // the oldest message beyond the total length is not sent again
+        let id = client.send_to(&message(100_000), addr).unwrap();
+
+        client.send_to(&message(100_000), addr).unwrap();
+
+        assert_eq!(client.kept_len, 100_000);
+
+        let err = client.recv_from().unwrap_err();
+
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<Undelivered>(),
+            Some(&Undelivered {
+                id,
+                err: ErrorCode::Overload
+            })
+        );
+
+        // the messages are forgotten after the timeout
+        thread::sleep(Duration::from_millis(20));
+        client.send_to(b"hello", addr).unwrap();
+
+        assert_eq!(client.sent.len(), 1);
+        assert_eq!(client.kept.len(), 1);
+        assert_eq!(client.kept_len, 5);
+    }
+
+    #[test]
+    fn connection() {
+        let fabric = Fabric::new();
+        let listener = fabric.node(1).unwrap().seq_packet().unwrap();
+        let client = fabric.node(2).unwrap().seq_packet().unwrap();
+
+        listener.bind(SERVICE).unwrap();
+        listener.listen().unwrap();
+        client.connect(SERVICE).unwrap();
+
+        let (conn, _) = listener.accept().unwrap();
+        let mut client = Connection::new(client);
+        let mut conn = Connection::new(conn);
+
+        client.send(&message(150_000)).unwrap();
+        client.send(b"hello").unwrap();
+
+        assert_eq!(conn.recv().unwrap(), Some(message(150_000)));
+        assert_eq!(conn.recv().unwrap(), Some(b"hello".to_vec()));
+
+        // a message too large is discarded
+        let mut conn = conn.max_len(100_000);
+
+        client.send(&message(150_000)).unwrap();
+        client.send(b"hello").unwrap();
+
+        assert_eq!(conn.recv().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(conn.recv().unwrap(), Some(b"hello".to_vec()));
+
+        client.get_ref().shutdown().unwrap();
+
+        assert_eq!(conn.recv().unwrap(), None);
+    }
+
+    #[test]
+    fn discarded() {
+        let fabric = Fabric::new();
+        let listener = fabric.node(1).unwrap().seq_packet().unwrap();
+        let client = fabric.node(2).unwrap().seq_packet().unwrap();
+
+        listener.bind(SERVICE).unwrap();
+        listener.listen().unwrap();
+        client.connect(SERVICE).unwrap();
+
+        let (conn, _) = listener.accept().unwrap();
+        let mut conn = Connection::new(conn).timeout(Duration::from_millis(10));
+        let fragment = |id, index| {
+            let mut buf = vec![];
+
+            Header {
+                id,
+                index,
+                count: 3,
+            }
+            .write(&mut buf);
+            client.send(&buf).unwrap();
+        };
+
+        // the sender stalls in the middle of a message
+        fragment(42, 0);
+
+        assert_eq!(conn.recv().unwrap_err().kind(), io::ErrorKind::TimedOut);
+
+        // a fragment is lost, the rest of the message is skipped
+        fragment(42, 1);
+        fragment(42, 2);
+        fragment(43, 0);
+        fragment(43, 2);
+        fragment(43, 1);
+
+        assert_eq!(conn.recv().unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // a new message interrupts a partial message, and is received next
+        fragment(44, 0);
+
+        let mut client = Connection::new(client);
+
+        client.send(b"hello").unwrap();
+
+        assert_eq!(conn.recv().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(conn.recv().unwrap(), Some(b"hello".to_vec()));
+    }
+}
@@ -1,8 +1,23 @@
 #![cfg(any(target_os = "linux", feature = "doc"))]
 
 mod addr;
+pub mod frag;
+pub mod netlink;
 mod sock;
 pub mod topo;
+pub mod transport;
+
+#[cfg(feature = "tokio")]
+pub mod aio;
+
+#[cfg(any(feature = "mock", feature = "netns"))]
+pub mod fault;
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+#[cfg(feature = "netns")]
+pub mod netns;
 
 #[allow(
     non_camel_case_types,
@@ -34,8 +49,8 @@ pub use addr::{
 };
 pub use sock::{
     bind, connect, connect_timeout, datagram, rdm, seq_packet, stream, Bindable, Bound, Buildable,
-    Builder, Connectable, Connected, Datagram, Group, Importance, Incoming, Join, Listener, Recv,
-    RecvMsg, Rejected, Send, SeqPacket, Socket, Stream, ToServiceAddrs, ToServiceRanges,
-    ToSocketAddrs, Wrapped,
+    Builder, Connectable, Connected, Datagram, Destination, ErrorCode, Group, Importance, Incoming,
+    Join, Listener, Recv, RecvMsg, RecvMsgBuf, Rejected, Send, SendMsg, SeqPacket, Socket, Stream,
+    ToServiceAddrs, ToServiceRanges, ToSocketAddrs, Truncated, Wrapped, MAX_BATCH,
 };
 pub use topo::wait;
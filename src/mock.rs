@@ -0,0 +1,2087 @@
+//! An in-memory simulated TIPC fabric, for testing without the `tipc` kernel module.
+//!
+//! A `Fabric` holds the nodes of a simulated cluster, the cluster wide binding table
+//! and the message queues of the sockets opened on the nodes.
+//! The sockets follow the semantics of the kernel implementation:
+//!
+//! - an anycast to a service address is delivered to one of the matching bindings in round-robin,
+//!   the bindings on the own node are preferred, and `EHOSTUNREACH` is returned if none matches;
+//! - a multicast to a service range is delivered to every socket with an overlapping binding;
+//! - a message which can't be delivered is returned to a rejectable sender as `RecvMsg::Rejected`,
+//!   otherwise it is dropped silently;
+//! - the group members receive `RecvMsg::MemberJoin` and `RecvMsg::MemberLeave` events;
+//! - the topology servers report the changes of the binding table and the nodes.
+//!
+//! The sockets are dropped like the kernel ones, their bindings are withdrawn,
+//! their groups are left and their connections are shut down.
+//!
+//! The faults injected into the fabric, see `fault::Inject`, take effect like on a real cluster:
+//! the bindings of the unreachable nodes are withdrawn, the members leave the groups,
+//! the connections are aborted with `TIPC_ERR_NO_NODE`, and so are the messages sent to them.
+
+use core::cmp;
+use core::time::Duration;
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::io;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use failure::format_err;
+
+use crate::{
+    addr::{
+        Instance, Scope, ServiceAddr, ServiceRange, SocketAddr, Visibility, TIPC_SERVICE_ADDR,
+        TIPC_SERVICE_RANGE, TIPC_SOCKET_ADDR,
+    },
+    fault::{Fault, Impairment, Inject},
+    ffi,
+    netlink::Publication,
+    sock::{addr_not_available, Destination, ErrorCode, Join, RecvMsg, Rejected},
+    sock::{Recv, ToSocketAddrs},
+    sock::{ToServiceAddrs, ToServiceRanges, Truncated, MAX_REJECTED_DATA},
+    topo::{self, Event, Expired, Filter, Subscription},
+    transport,
+};
+
+/// The default receive buffer size of the sockets.
+const RECV_BUF_SIZE: usize = 2 * 1024 * 1024;
+
+/// A simulated cluster.
+#[derive(Clone, Debug, Default)]
+pub struct Fabric(Arc<Shared>);
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+impl Fabric {
+    /// Creates an empty cluster.
+    pub fn new() -> Self {
+        Fabric::default()
+    }
+
+    /// Adds the node with the address to the cluster, or returns the existing one.
+    pub fn node(&self, addr: Instance) -> io::Result<Node> {
+        if addr == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid node address",
+            ));
+        }
+
+        self.with(|state| {
+            if state.net.nodes.insert(addr) {
+                state.publish(
+                    ServiceRange::new(ffi::TIPC_CFG_SRV, addr, addr),
+                    SocketAddr::new(0, addr),
+                    Visibility::Cluster,
+                );
+            }
+        });
+
+        Ok(Node {
+            fabric: self.clone(),
+            addr,
+        })
+    }
+
+    /// Returns the addresses of the nodes in the cluster.
+    pub fn nodes(&self) -> Vec<Instance> {
+        self.lock().net.nodes.iter().cloned().collect()
+    }
+
+    /// Seeds the pseudo-random generator deciding which messages are lost or reordered.
+    pub fn seed(&self, seed: u64) {
+        self.lock().seed = seed;
+    }
+
+    /// Dumps the cluster wide binding table.
+    pub fn name_table(&self) -> Vec<Publication> {
+        self.lock()
+            .bindings
+            .iter()
+            .map(|b| Publication {
+                service: b.service,
+                sock: b.sock,
+                node: b.sock.node().into(),
+                scope: b.visibility,
+                key: b.key,
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.0.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Changes the state and wakes up the blocked receivers.
+    fn with<R, F: FnOnce(&mut State) -> R>(&self, f: F) -> R {
+        let mut state = self.lock();
+
+        state.flush();
+
+        let res = f(&mut state);
+
+        drop(state);
+
+        self.0.cond.notify_all();
+
+        res
+    }
+
+    /// Polls the state until a result is ready, or the socket would block.
+    fn wait<R, F>(&self, sock: SocketAddr, dont_wait: bool, mut poll: F) -> io::Result<R>
+    where
+        F: FnMut(&mut State) -> io::Result<Option<R>>,
+    {
+        let started = Instant::now();
+        let mut state = self.lock();
+
+        loop {
+            state.flush();
+
+            if let Some(res) = poll(&mut state)? {
+                drop(state);
+                self.0.cond.notify_all();
+
+                return Ok(res);
+            }
+
+            let port = state.port(sock)?;
+
+            let timeout = port.read_timeout.map(|timeout| started + timeout);
+
+            if dont_wait || port.nonblocking || timeout.is_some_and(|t| t <= Instant::now()) {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+
+            let deadline = port
+                .subs
+                .iter()
+                .filter_map(|&(_, deadline)| deadline)
+                .chain(state.pending.iter().map(|p| p.due))
+                .chain(timeout)
+                .min();
+
+            state = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+
+                    self.0
+                        .cond
+                        .wait_timeout(state, timeout)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => self
+                    .0
+                    .cond
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+}
+
+/// A node in the simulated cluster.
+#[derive(Clone, Debug)]
+pub struct Node {
+    fabric: Fabric,
+    addr: Instance,
+}
+
+impl Node {
+    /// The node address.
+    pub fn addr(&self) -> Instance {
+        self.addr
+    }
+
+    /// The cluster of the node.
+    pub fn fabric(&self) -> &Fabric {
+        &self.fabric
+    }
+
+    /// Creates a reliable datagram socket on the node.
+    pub fn rdm(&self) -> io::Result<Datagram> {
+        self.open(Kind::Datagram).map(Datagram)
+    }
+
+    /// Creates a datagram socket on the node.
+    pub fn datagram(&self) -> io::Result<Datagram> {
+        self.rdm()
+    }
+
+    /// Creates a sequenced packet socket on the node.
+    pub fn seq_packet(&self) -> io::Result<SeqPacket> {
+        self.open(Kind::SeqPacket).map(SeqPacket)
+    }
+
+    /// Connects to the topology service of the node.
+    pub fn topology(&self) -> io::Result<Server> {
+        self.open(Kind::Topology).map(Server)
+    }
+
+    /// Subscribe events for neighbor nodes.
+    pub fn neighbor_nodes(&self) -> io::Result<Nodes> {
+        let srv = self.topology()?;
+
+        srv.subscribe(topo::NEIGHBOR_NODES)?;
+
+        Ok(Nodes(srv))
+    }
+
+    fn open(&self, kind: Kind) -> io::Result<Handle> {
+        let addr = self.addr;
+
+        self.fabric
+            .with(|state| state.open(addr, kind))
+            .map(|addr| Handle {
+                fabric: self.fabric.clone(),
+                addr,
+            })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Datagram,
+    SeqPacket,
+    Topology,
+}
+
+#[derive(Debug)]
+enum Packet {
+    Data {
+        from: SocketAddr,
+        service: Option<ServiceRange>,
+        data: Vec<u8>,
+    },
+    Rejected {
+        err: u32,
+        dest: Destination,
+        data: Vec<u8>,
+    },
+    Member {
+        join: bool,
+        member: ServiceAddr,
+        sock: SocketAddr,
+    },
+    Event(Event),
+}
+
+#[derive(Debug)]
+enum Conn {
+    Idle,
+    Listening(VecDeque<SocketAddr>),
+    Connected(SocketAddr),
+    Disconnected(SocketAddr),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Member {
+    service: ServiceAddr,
+    visibility: Visibility,
+    flags: Join,
+    key: u32,
+}
+
+#[derive(Debug)]
+struct Port {
+    kind: Kind,
+    nonblocking: bool,
+    read_timeout: Option<Duration>,
+    rejectable: bool,
+    recv_buf_size: usize,
+    queued: usize,
+    queue: VecDeque<Packet>,
+    group: Option<Member>,
+    conn: Conn,
+    subs: Vec<(Subscription, Option<Instant>)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Binding {
+    service: ServiceRange,
+    sock: SocketAddr,
+    visibility: Visibility,
+    key: u32,
+}
+
+impl Binding {
+    fn overlaps(&self, service: &ServiceRange) -> bool {
+        self.service.ty() == service.ty()
+            && self.service.lower() <= service.upper()
+            && service.lower() <= self.service.upper()
+    }
+}
+
+/// The nodes and the links between them.
+#[derive(Debug, Default)]
+struct Net {
+    nodes: BTreeSet<Instance>,
+    /// The node pairs whose link is down, the lower address first.
+    down: BTreeSet<(Instance, Instance)>,
+}
+
+impl Net {
+    fn reachable(&self, from: Instance, to: Instance) -> bool {
+        self.nodes.contains(&from)
+            && self.nodes.contains(&to)
+            && (from == to || !self.down.contains(&(from.min(to), from.max(to))))
+    }
+
+    /// The binding is visible from the node.
+    fn visible(&self, binding: &Binding, node: Instance) -> bool {
+        (binding.visibility != Visibility::Node || binding.sock.node() == node)
+            && self.reachable(node, binding.sock.node())
+    }
+}
+
+/// A delayed message.
+#[derive(Debug)]
+struct Pending {
+    due: Instant,
+    from: SocketAddr,
+    to: SocketAddr,
+    service: Option<ServiceRange>,
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    net: Net,
+    socks: HashMap<SocketAddr, Port>,
+    bindings: Vec<Binding>,
+    impairments: HashMap<(Instance, Instance), Impairment>,
+    pending: Vec<Pending>,
+    next_port: u32,
+    next_key: u32,
+    round_robin: usize,
+    seed: u64,
+}
+
+fn bad_socket() -> io::Error {
+    io::Error::from_raw_os_error(libc::EBADF)
+}
+
+fn unreachable() -> io::Error {
+    io::Error::from_raw_os_error(libc::EHOSTUNREACH)
+}
+
+/// The visibility of the binding for the scope of the address.
+fn visibility(scope: Scope, node: Instance) -> Visibility {
+    match scope {
+        Scope::Node(n) if n.get() == node => Visibility::Node,
+        _ => Visibility::default(),
+    }
+}
+
+fn recv_from(res: (RecvMsg, SocketAddr), flags: Recv) -> io::Result<(usize, SocketAddr)> {
+    match res {
+        (RecvMsg::Message { len, truncated, .. }, addr) => {
+            Truncated::check(len, truncated, flags).map(|len| (len, addr))
+        }
+        (RecvMsg::Rejected(rejected), _) => Err(rejected.err.into()),
+        (msg, _) => Err(io::Error::new(
+            io::ErrorKind::Other,
+            format_err!("unexpected group event: {:?}", msg),
+        )),
+    }
+}
+
+impl Port {
+    /// Reports the binding to the matching subscriptions,
+    /// an edge event is only reported for the first or last binding of a service range.
+    fn report(&mut self, binding: &Binding, published: bool, edge: bool) {
+        for &(subscription, _) in &self.subs {
+            if !binding.overlaps(&subscription.service)
+                || (subscription.filter == Filter::Edge && !edge)
+            {
+                continue;
+            }
+
+            let service = ServiceRange::new(
+                binding.service.ty(),
+                cmp::max(binding.service.lower(), subscription.service.lower()),
+                cmp::min(binding.service.upper(), subscription.service.upper()),
+            );
+
+            self.queue.push_back(Packet::Event(if published {
+                Event::Published {
+                    service,
+                    sock: binding.sock,
+                    subscription,
+                }
+            } else {
+                Event::Withdrawn {
+                    service,
+                    sock: binding.sock,
+                    subscription,
+                }
+            }));
+        }
+    }
+}
+
+impl State {
+    fn port(&self, sock: SocketAddr) -> io::Result<&Port> {
+        self.socks.get(&sock).ok_or_else(bad_socket)
+    }
+
+    fn port_mut(&mut self, sock: SocketAddr) -> io::Result<&mut Port> {
+        self.socks.get_mut(&sock).ok_or_else(bad_socket)
+    }
+
+    fn open(&mut self, node: Instance, kind: Kind) -> io::Result<SocketAddr> {
+        if !self.net.nodes.contains(&node) {
+            return Err(unreachable());
+        }
+
+        self.next_port += 1;
+
+        let addr = SocketAddr::new(self.next_port, node);
+
+        self.socks.insert(
+            addr,
+            Port {
+                kind,
+                nonblocking: false,
+                read_timeout: None,
+                rejectable: false,
+                recv_buf_size: RECV_BUF_SIZE,
+                queued: 0,
+                queue: VecDeque::new(),
+                group: None,
+                conn: Conn::Idle,
+                subs: vec![],
+            },
+        );
+
+        Ok(addr)
+    }
+
+    fn close(&mut self, sock: SocketAddr) {
+        let mut closing = vec![sock];
+
+        while let Some(sock) = closing.pop() {
+            let _ = self.leave(sock);
+
+            let port = match self.socks.remove(&sock) {
+                Some(port) => port,
+                None => continue,
+            };
+
+            self.withdraw(|b| b.sock == sock);
+
+            match port.conn {
+                Conn::Connected(peer) => self.disconnect(peer, sock, ffi::TIPC_CONN_SHUTDOWN),
+                Conn::Listening(backlog) => closing.extend(backlog),
+                _ => {}
+            }
+        }
+    }
+
+    /// Aborts the connection of the socket to the peer with the error code.
+    fn disconnect(&mut self, sock: SocketAddr, peer: SocketAddr, err: u32) {
+        if let Some(port) = self.socks.get_mut(&sock) {
+            port.conn = Conn::Disconnected(peer);
+            port.queue.push_back(Packet::Rejected {
+                err,
+                dest: Destination::Socket(peer),
+                data: vec![],
+            });
+        }
+    }
+
+    fn publish(&mut self, service: ServiceRange, sock: SocketAddr, visibility: Visibility) -> u32 {
+        self.next_key += 1;
+
+        let binding = Binding {
+            service,
+            sock,
+            visibility,
+            key: self.next_key,
+        };
+
+        self.bindings.push(binding);
+        self.report(&binding, true);
+
+        binding.key
+    }
+
+    fn withdraw<F: Fn(&Binding) -> bool>(&mut self, pred: F) {
+        let (withdrawn, bindings) = self.bindings.drain(..).partition(|b| pred(b));
+
+        self.bindings = bindings;
+
+        for binding in withdrawn {
+            self.report(&binding, false);
+        }
+    }
+
+    /// Reports the binding to the matching subscriptions of the topology servers.
+    fn report(&mut self, binding: &Binding, published: bool) {
+        let State {
+            net,
+            socks,
+            bindings,
+            ..
+        } = self;
+
+        for (addr, port) in socks.iter_mut() {
+            if port.kind != Kind::Topology || !net.visible(binding, addr.node()) {
+                continue;
+            }
+
+            let edge = !bindings.iter().any(|b| {
+                b.key != binding.key && b.service == binding.service && net.visible(b, addr.node())
+            });
+
+            port.report(binding, published, edge);
+        }
+    }
+
+    /// The bindings visible from the topology servers.
+    fn views(&self) -> Vec<(SocketAddr, Vec<Binding>)> {
+        self.socks
+            .iter()
+            .filter(|(_, port)| port.kind == Kind::Topology)
+            .map(|(&addr, _)| {
+                let visible = self
+                    .bindings
+                    .iter()
+                    .filter(|b| self.net.visible(b, addr.node()))
+                    .cloned()
+                    .collect();
+
+                (addr, visible)
+            })
+            .collect()
+    }
+
+    /// The pairs of the group members visible to each other, with the service address of the latter.
+    fn member_pairs(&self) -> Vec<(SocketAddr, SocketAddr, ServiceAddr)> {
+        self.socks
+            .iter()
+            .filter_map(|(&addr, port)| port.group.map(|group| (addr, group)))
+            .flat_map(|(addr, group)| {
+                self.members(addr, &group)
+                    .into_iter()
+                    .map(move |(peer, member)| (addr, peer, member.service))
+            })
+            .collect()
+    }
+
+    /// Changes the nodes or the links, and reports the bindings, group members and connections affected.
+    fn change<F: FnOnce(&mut State)>(&mut self, f: F) {
+        let views = self.views();
+        let pairs = self.member_pairs();
+
+        f(self);
+
+        for (addr, before) in views {
+            let after = self
+                .bindings
+                .iter()
+                .filter(|b| self.net.visible(b, addr.node()))
+                .cloned()
+                .collect::<Vec<_>>();
+            let port = match self.socks.get_mut(&addr) {
+                Some(port) => port,
+                None => continue,
+            };
+            let mut current = before.clone();
+
+            for b in before
+                .iter()
+                .filter(|b| after.iter().all(|a| a.key != b.key))
+            {
+                current.retain(|c| c.key != b.key);
+                port.report(b, false, current.iter().all(|c| c.service != b.service));
+            }
+            for b in after
+                .iter()
+                .filter(|a| before.iter().all(|b| b.key != a.key))
+            {
+                port.report(b, true, current.iter().all(|c| c.service != b.service));
+                current.push(*b);
+            }
+        }
+
+        let now = self.member_pairs();
+
+        for (join, changed) in &[(false, &pairs), (true, &now)] {
+            let other = if *join { &pairs } else { &now };
+
+            for &(addr, peer, member) in changed.iter() {
+                if other.iter().any(|&(a, p, _)| a == addr && p == peer) {
+                    continue;
+                }
+                if let Some(port) = self.socks.get_mut(&addr) {
+                    if port
+                        .group
+                        .is_some_and(|g| g.flags.contains(Join::MEMBER_EVTS))
+                    {
+                        port.queue.push_back(Packet::Member {
+                            join: *join,
+                            member,
+                            sock: peer,
+                        });
+                    }
+                }
+            }
+        }
+
+        let aborted = self
+            .socks
+            .iter()
+            .filter_map(|(&addr, port)| match port.conn {
+                Conn::Connected(peer)
+                    if !self.socks.contains_key(&peer)
+                        || !self.net.reachable(addr.node(), peer.node()) =>
+                {
+                    Some((addr, peer))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for (addr, peer) in aborted {
+            self.disconnect(addr, peer, ffi::TIPC_ERR_NO_NODE);
+        }
+    }
+
+    /// Kills the node, its sockets are gone without notifying the peers.
+    fn kill(&mut self, node: Instance) {
+        self.change(|state| {
+            state.net.nodes.remove(&node);
+            state.net.down.retain(|&(a, b)| a != node && b != node);
+            state.socks.retain(|addr, _| addr.node() != node);
+            state.bindings.retain(|b| b.sock.node() != node);
+            state.pending.retain(|p| p.from.node() != node);
+        })
+    }
+
+    /// Returns true with the percentage of chance.
+    fn chance(&mut self, percent: f64) -> bool {
+        if percent <= 0.0 {
+            return false;
+        }
+        if self.seed == 0 {
+            self.seed = 0x2545_F491_4F6C_DD1D;
+        }
+
+        // xorshift64
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        ((self.seed % 10_000) as f64) < percent * 100.0
+    }
+
+    /// Delivers the delayed messages which are due.
+    fn flush(&mut self) {
+        let now = Instant::now();
+
+        if self.pending.iter().all(|p| p.due > now) {
+            return;
+        }
+
+        let (mut due, pending): (Vec<_>, _) = self.pending.drain(..).partition(|p| p.due <= now);
+
+        self.pending = pending;
+        due.sort_by_key(|p| p.due);
+
+        for p in due {
+            self.enqueue(p.from, p.to, p.service, p.data);
+        }
+    }
+
+    /// Finds a socket for the anycast to the service address.
+    fn lookup(&mut self, node: Instance, service: ServiceAddr, scope: Scope) -> Option<SocketAddr> {
+        let mut matches = self
+            .bindings
+            .iter()
+            .filter(|b| {
+                self.net.visible(b, node)
+                    && b.sock.port() != 0
+                    && b.overlaps(&service.into())
+                    && (scope == Scope::Global || scope == b.sock.node())
+            })
+            .map(|b| b.sock)
+            .collect::<Vec<_>>();
+
+        if scope == Scope::Global && matches.iter().any(|sock| sock.node() == node) {
+            matches.retain(|sock| sock.node() == node);
+        }
+
+        if matches.is_empty() {
+            None
+        } else {
+            self.round_robin = self.round_robin.wrapping_add(1);
+
+            Some(matches[self.round_robin % matches.len()])
+        }
+    }
+
+    /// Finds the sockets for the multicast to the service range.
+    fn lookup_all(&self, node: Instance, service: ServiceRange) -> Vec<SocketAddr> {
+        let mut socks = vec![];
+
+        for b in &self.bindings {
+            if self.net.visible(b, node)
+                && b.sock.port() != 0
+                && b.overlaps(&service)
+                && !socks.contains(&b.sock)
+            {
+                socks.push(b.sock);
+            }
+        }
+
+        socks
+    }
+
+    /// Returns the head of the message to the sender if it is rejectable, or drops it.
+    fn reject(
+        &mut self,
+        from: SocketAddr,
+        to: SocketAddr,
+        err: u32,
+        service: Option<ServiceRange>,
+        mut data: Vec<u8>,
+    ) {
+        if let Some(port) = self.socks.get_mut(&from) {
+            if port.rejectable {
+                data.truncate(MAX_REJECTED_DATA);
+
+                port.queue.push_back(Packet::Rejected {
+                    err,
+                    dest: service.map_or(Destination::Socket(to), Destination::Service),
+                    data,
+                });
+            }
+        }
+    }
+
+    /// Delivers the message through the impaired link.
+    fn deliver(
+        &mut self,
+        from: SocketAddr,
+        to: SocketAddr,
+        service: Option<ServiceRange>,
+        data: Vec<u8>,
+    ) {
+        if let Some(&impairment) = self.impairments.get(&(from.node(), to.node())) {
+            if self.chance(impairment.loss) {
+                return;
+            }
+            if impairment.delay > Duration::default() && !self.chance(impairment.reorder) {
+                self.pending.push(Pending {
+                    due: Instant::now() + impairment.delay,
+                    from,
+                    to,
+                    service,
+                    data,
+                });
+
+                return;
+            }
+        }
+
+        self.enqueue(from, to, service, data)
+    }
+
+    fn enqueue(
+        &mut self,
+        from: SocketAddr,
+        to: SocketAddr,
+        service: Option<ServiceRange>,
+        data: Vec<u8>,
+    ) {
+        if !self.net.reachable(from.node(), to.node()) {
+            return self.reject(from, to, ffi::TIPC_ERR_NO_NODE, service, data);
+        }
+
+        match self.socks.get_mut(&to) {
+            Some(port) if port.kind != Kind::Topology => {
+                if port.queued + data.len() > port.recv_buf_size {
+                    self.reject(from, to, ffi::TIPC_ERR_OVERLOAD, service, data)
+                } else {
+                    port.queued += data.len();
+                    port.queue.push_back(Packet::Data {
+                        from,
+                        service,
+                        data,
+                    });
+                }
+            }
+            _ => self.reject(from, to, ffi::TIPC_ERR_NO_PORT, service, data),
+        }
+    }
+
+    fn send_to(&mut self, from: SocketAddr, sa: ffi::sockaddr_tipc, buf: &[u8]) -> io::Result<()> {
+        self.port(from)?;
+
+        match sa.addrtype {
+            TIPC_SOCKET_ADDR => {
+                let to: SocketAddr = unsafe { sa.addr.id }.into();
+
+                if !self.net.reachable(from.node(), to.node()) {
+                    return Err(unreachable());
+                }
+
+                self.deliver(from, to, None, buf.to_vec());
+            }
+            TIPC_SERVICE_ADDR => {
+                let service: ServiceAddr = unsafe { sa.addr.name.name }.into();
+                let scope = Scope::new(unsafe { sa.addr.name.domain });
+                let to = self
+                    .lookup(from.node(), service, scope)
+                    .ok_or_else(unreachable)?;
+
+                self.deliver(from, to, Some(service.into()), buf.to_vec());
+            }
+            TIPC_SERVICE_RANGE => {
+                let service: ServiceRange = unsafe { sa.addr.nameseq }.into();
+
+                for to in self.lookup_all(from.node(), service) {
+                    self.deliver(from, to, Some(service), buf.to_vec());
+                }
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "invalid address type",
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Receives a message from the queue, copying its data to the buffer.
+    fn recv_msg(
+        &mut self,
+        sock: SocketAddr,
+        buf: &mut [u8],
+        flags: Recv,
+    ) -> io::Result<Option<(RecvMsg, SocketAddr)>> {
+        let port = self.port_mut(sock)?;
+
+        let res = match port.queue.front() {
+            Some(Packet::Data {
+                from,
+                service,
+                data,
+            }) => {
+                let len = buf.len().min(data.len());
+
+                buf[..len].copy_from_slice(&data[..len]);
+
+                (
+                    RecvMsg::Message {
+                        len,
+                        service: *service,
+                        truncated: data.len() > buf.len(),
+                    },
+                    *from,
+                )
+            }
+            Some(Packet::Rejected { err, dest, data }) => {
+                let len = buf.len().min(data.len());
+
+                buf[..len].copy_from_slice(&data[..len]);
+
+                (
+                    RecvMsg::Rejected(Rejected {
+                        err: (*err).into(),
+                        len: data.len(),
+                        truncated: data.len() > buf.len(),
+                        dest: *dest,
+                    }),
+                    sock,
+                )
+            }
+            Some(&Packet::Member { join, member, sock }) => (
+                if join {
+                    RecvMsg::MemberJoin(member)
+                } else {
+                    RecvMsg::MemberLeave(member)
+                },
+                sock,
+            ),
+            Some(Packet::Event(_)) | None => return Ok(None),
+        };
+
+        if !flags.contains(Recv::PEEK) {
+            if let Some(Packet::Data { data, .. }) = port.queue.pop_front() {
+                port.queued -= data.len();
+            }
+        }
+
+        Ok(Some(res))
+    }
+
+    /// The length of the data of the next message, `Some(None)` for a membership event.
+    fn next_message_len(&self, sock: SocketAddr) -> io::Result<Option<Option<usize>>> {
+        Ok(match self.port(sock)?.queue.front() {
+            Some(Packet::Data { data, .. }) | Some(Packet::Rejected { data, .. }) => {
+                Some(Some(data.len()))
+            }
+            Some(Packet::Member { .. }) => Some(None),
+            Some(Packet::Event(_)) | None => None,
+        })
+    }
+
+    fn bind<A: ToServiceRanges>(&mut self, sock: SocketAddr, addr: A) -> io::Result<()> {
+        self.port(sock)?;
+
+        for (service, visibility) in addr.to_service_ranges()? {
+            if service.ty() < ffi::TIPC_RESERVED_TYPES || service.lower() > service.upper() {
+                return Err(io::Error::from_raw_os_error(libc::EACCES));
+            }
+
+            self.publish(service, sock, visibility);
+        }
+
+        Ok(())
+    }
+
+    fn unbind<A: ToServiceRanges>(&mut self, sock: SocketAddr, addr: A) -> io::Result<()> {
+        self.port(sock)?;
+
+        for (service, _) in addr.to_service_ranges()? {
+            self.withdraw(|b| b.sock == sock && b.service == service);
+        }
+
+        Ok(())
+    }
+
+    /// The group members visible to each other.
+    fn members(&self, sock: SocketAddr, member: &Member) -> Vec<(SocketAddr, Member)> {
+        self.socks
+            .iter()
+            .filter_map(|(&addr, port)| port.group.map(|group| (addr, group)))
+            .filter(|&(addr, group)| {
+                addr != sock
+                    && self.net.reachable(addr.node(), sock.node())
+                    && group.service.ty() == member.service.ty()
+                    && ((group.visibility != Visibility::Node
+                        && member.visibility != Visibility::Node)
+                        || addr.node() == sock.node())
+            })
+            .collect()
+    }
+
+    /// Notifies the group members of the membership change.
+    fn member_events(&mut self, sock: SocketAddr, member: Member, join: bool) {
+        for (addr, group) in self.members(sock, &member) {
+            if group.flags.contains(Join::MEMBER_EVTS) {
+                self.socks
+                    .get_mut(&addr)
+                    .unwrap()
+                    .queue
+                    .push_back(Packet::Member {
+                        join,
+                        member: member.service,
+                        sock,
+                    });
+            }
+            if member.flags.contains(Join::MEMBER_EVTS) {
+                if let Some(port) = self.socks.get_mut(&sock) {
+                    port.queue.push_back(Packet::Member {
+                        join,
+                        member: group.service,
+                        sock: addr,
+                    });
+                }
+            }
+        }
+    }
+
+    fn join<A: ToServiceAddrs>(
+        &mut self,
+        sock: SocketAddr,
+        addr: A,
+        flags: Join,
+    ) -> io::Result<()> {
+        let (service, scope) = addr
+            .to_service_addrs()?
+            .next()
+            .ok_or_else(addr_not_available)?;
+
+        if self.port(sock)?.group.is_some() {
+            return Err(io::Error::from_raw_os_error(libc::EACCES));
+        }
+
+        let visibility = visibility(scope, sock.node());
+        let key = self.publish(service.into(), sock, visibility);
+        let member = Member {
+            service,
+            visibility,
+            flags,
+            key,
+        };
+
+        self.port_mut(sock)?.group = Some(member);
+        self.member_events(sock, member, true);
+
+        Ok(())
+    }
+
+    fn leave(&mut self, sock: SocketAddr) -> io::Result<()> {
+        let member = self
+            .port_mut(sock)?
+            .group
+            .take()
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::EINVAL))?;
+
+        self.withdraw(|b| b.key == member.key);
+        self.member_events(sock, member, false);
+
+        Ok(())
+    }
+
+    /// Sends the message to the group members matching the destination.
+    fn group_send(
+        &mut self,
+        from: SocketAddr,
+        dst: Option<ffi::sockaddr_tipc>,
+        buf: &[u8],
+    ) -> io::Result<()> {
+        let member = self
+            .port(from)?
+            .group
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::EINVAL))?;
+        let mut members = self.members(from, &member);
+
+        if member.flags.contains(Join::LOOPBACK) {
+            members.push((from, member));
+        }
+
+        let mut targets = match dst {
+            None => members.into_iter().map(|(addr, _)| addr).collect(),
+            Some(sa) => match sa.addrtype {
+                TIPC_SOCKET_ADDR => {
+                    let to: SocketAddr = unsafe { sa.addr.id }.into();
+
+                    members
+                        .into_iter()
+                        .map(|(addr, _)| addr)
+                        .filter(|&addr| addr == to)
+                        .collect()
+                }
+                TIPC_SERVICE_ADDR => {
+                    let instance = unsafe { sa.addr.name.name.instance };
+                    let mut matches = members
+                        .into_iter()
+                        .filter(|(_, group)| group.service.instance() == instance)
+                        .map(|(addr, _)| addr)
+                        .collect::<Vec<_>>();
+
+                    if matches.is_empty() {
+                        matches
+                    } else {
+                        self.round_robin = self.round_robin.wrapping_add(1);
+
+                        vec![matches.swap_remove(self.round_robin % matches.len())]
+                    }
+                }
+                TIPC_SERVICE_RANGE => {
+                    let service: ServiceRange = unsafe { sa.addr.nameseq }.into();
+
+                    members
+                        .into_iter()
+                        .filter(|(_, group)| {
+                            (service.lower()..=service.upper()).contains(&group.service.instance())
+                        })
+                        .map(|(addr, _)| addr)
+                        .collect()
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "invalid address type",
+                    ))
+                }
+            },
+        };
+
+        if dst.is_some() && targets.is_empty() {
+            return Err(unreachable());
+        }
+
+        for to in targets.drain(..) {
+            self.deliver(from, to, None, buf.to_vec());
+        }
+
+        Ok(())
+    }
+
+    fn connect<A: ToServiceAddrs>(&mut self, sock: SocketAddr, addr: A) -> io::Result<()> {
+        let (service, scope) = addr
+            .to_service_addrs()?
+            .next()
+            .ok_or_else(addr_not_available)?;
+
+        match self.port(sock)?.conn {
+            Conn::Idle => {}
+            Conn::Listening(_) => return Err(io::Error::from_raw_os_error(libc::EINVAL)),
+            _ => return Err(io::Error::from_raw_os_error(libc::EISCONN)),
+        }
+
+        let listener = self
+            .lookup(sock.node(), service, scope)
+            .ok_or_else(unreachable)?;
+
+        match self.port(listener)? {
+            Port {
+                kind: Kind::SeqPacket,
+                conn: Conn::Listening(_),
+                ..
+            } => {}
+            _ => return Err(io::ErrorKind::ConnectionRefused.into()),
+        }
+
+        let accepted = self.open(listener.node(), Kind::SeqPacket)?;
+
+        self.port_mut(accepted)?.conn = Conn::Connected(sock);
+        self.port_mut(sock)?.conn = Conn::Connected(accepted);
+
+        if let Conn::Listening(ref mut backlog) = self.port_mut(listener)?.conn {
+            backlog.push_back(accepted);
+        }
+
+        Ok(())
+    }
+
+    fn subscribe(&mut self, sock: SocketAddr, subscription: Subscription) -> io::Result<()> {
+        let deadline = subscription.timeout.map(|timeout| Instant::now() + timeout);
+        let node = sock.node();
+        let port = self.socks.get_mut(&sock).ok_or_else(bad_socket)?;
+
+        port.subs.push((subscription, deadline));
+
+        // Reports the existing bindings, only one for each service range if the edge events are wanted.
+        let mut reported: Vec<ServiceRange> = vec![];
+
+        for b in &self.bindings {
+            if !self.net.visible(b, node) || !b.overlaps(&subscription.service) {
+                continue;
+            }
+            if subscription.filter == Filter::Edge {
+                if reported.contains(&b.service) {
+                    continue;
+                }
+
+                reported.push(b.service);
+            }
+
+            port.queue.push_back(Packet::Event(Event::Published {
+                service: ServiceRange::new(
+                    b.service.ty(),
+                    cmp::max(b.service.lower(), subscription.service.lower()),
+                    cmp::min(b.service.upper(), subscription.service.upper()),
+                ),
+                sock: b.sock,
+                subscription,
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+/// A simulated socket, which is closed when dropped.
+#[derive(Debug)]
+struct Handle {
+    fabric: Fabric,
+    addr: SocketAddr,
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        let addr = self.addr;
+
+        self.fabric.with(|state| state.close(addr));
+    }
+}
+
+impl Handle {
+    fn with<R, F>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&mut State, SocketAddr) -> io::Result<R>,
+    {
+        let addr = self.addr;
+
+        self.fabric.with(|state| f(state, addr))
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.with(|state, addr| {
+            state.port_mut(addr)?.nonblocking = nonblocking;
+
+            Ok(())
+        })
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        if timeout == Some(Duration::default()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot set a 0 duration timeout",
+            ));
+        }
+
+        self.with(|state, addr| {
+            state.port_mut(addr)?.read_timeout = timeout;
+
+            Ok(())
+        })
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.with(|state, addr| state.port(addr).map(|_| addr))
+    }
+
+    fn recv_msg(&self, buf: &mut [u8], flags: Recv) -> io::Result<(RecvMsg, SocketAddr)> {
+        let addr = self.addr;
+
+        self.fabric
+            .wait(addr, flags.contains(Recv::DONT_WAIT), |state| {
+                state.recv_msg(addr, buf, flags)
+            })
+    }
+
+    fn next_message_len(&self) -> io::Result<Option<usize>> {
+        let addr = self.addr;
+
+        self.fabric
+            .wait(addr, false, |state| state.next_message_len(addr))
+    }
+}
+
+/// A simulated datagram socket.
+#[derive(Debug)]
+pub struct Datagram(Handle);
+
+impl Datagram {
+    /// Binds this socket to the specified address.
+    pub fn bind<A: ToServiceRanges>(&self, addr: A) -> io::Result<()> {
+        self.0.with(|state, sock| state.bind(sock, addr))
+    }
+
+    /// Unbinds this socket from the specified address.
+    pub fn unbind<A: ToServiceRanges>(&self, addr: A) -> io::Result<()> {
+        self.0.with(|state, sock| state.unbind(sock, addr))
+    }
+
+    /// Moves this socket into or out of nonblocking mode.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.0.set_nonblocking(nonblocking)
+    }
+
+    /// Sets the read timeout, the receive calls fail with `WouldBlock` after the timeout.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.0.set_read_timeout(timeout)
+    }
+
+    /// The undeliverable messages will be returned to this socket instead of being dropped.
+    pub fn set_rejectable(&self, rejectable: bool) -> io::Result<()> {
+        self.0.with(|state, sock| {
+            state.port_mut(sock)?.rejectable = rejectable;
+
+            Ok(())
+        })
+    }
+
+    /// Sets the size of the receive queue, the messages beyond it are rejected as overloaded.
+    pub fn set_recv_buf_size(&self, size: i32) -> io::Result<()> {
+        self.0.with(|state, sock| {
+            state.port_mut(sock)?.recv_buf_size = size.max(0) as usize;
+
+            Ok(())
+        })
+    }
+
+    /// Returns the address of the local half of this socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.local_addr()
+    }
+
+    /// Receives data from the socket.
+    ///
+    /// On success, returns the number of bytes read and the address from whence the data came.
+    pub fn recv_from<T: AsMut<[u8]>>(&self, mut buf: T) -> io::Result<(usize, SocketAddr)> {
+        recv_from(self.0.recv_msg(buf.as_mut(), Recv::empty())?, Recv::empty())
+    }
+
+    /// Receives a single datagram message on the socket, without removing it from the queue.
+    pub fn peek_from<T: AsMut<[u8]>>(&self, mut buf: T) -> io::Result<(usize, SocketAddr)> {
+        recv_from(self.0.recv_msg(buf.as_mut(), Recv::PEEK)?, Recv::PEEK)
+    }
+
+    /// Returns the length of the next message, without removing it from the queue.
+    ///
+    /// `None` is returned for a group membership event.
+    pub fn next_message_len(&self) -> io::Result<Option<usize>> {
+        self.0.next_message_len()
+    }
+
+    /// Receives a message, a returned undelivered message or a group membership event.
+    pub fn recv_msg<T: AsMut<[u8]>>(
+        &self,
+        mut buf: T,
+        flags: Recv,
+    ) -> io::Result<(RecvMsg, SocketAddr)> {
+        self.0.recv_msg(buf.as_mut(), flags)
+    }
+
+    /// Sends data on the socket to the given address.
+    ///
+    /// The destination may be a socket address, a service address for anycast,
+    /// or a service range for multicast.
+    pub fn send_to<T, A>(&self, buf: T, dst: A) -> io::Result<usize>
+    where
+        T: AsRef<[u8]>,
+        A: ToSocketAddrs,
+    {
+        let buf = buf.as_ref();
+        let sa = dst
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(addr_not_available)?
+            .into();
+
+        self.0
+            .with(|state, sock| state.send_to(sock, sa, buf))
+            .map(|_: ()| buf.len())
+    }
+
+    /// Join a communication group.
+    pub fn join<A: ToServiceAddrs>(&self, addr: A, flags: Join) -> io::Result<()> {
+        self.0.with(|state, sock| state.join(sock, addr, flags))
+    }
+
+    /// Leave a communication group.
+    pub fn leave(&self) -> io::Result<()> {
+        self.0.with(|state, sock| state.leave(sock))
+    }
+
+    /// Sends a broadcast message to all members of the group.
+    pub fn broadcast<B: AsRef<[u8]>>(&self, buf: B) -> io::Result<usize> {
+        let buf = buf.as_ref();
+
+        self.0
+            .with(|state, sock| state.group_send(sock, None, buf))
+            .map(|_: ()| buf.len())
+    }
+
+    /// Sends a multicast message to the group members in the service range.
+    pub fn multicast<B, A>(&self, buf: B, addr: A) -> io::Result<usize>
+    where
+        B: AsRef<[u8]>,
+        A: ToSocketAddrs,
+    {
+        self.anycast(buf, addr)
+    }
+
+    /// Sends a anycast message to one of the group members matching the service address.
+    pub fn anycast<B, A>(&self, buf: B, dst: A) -> io::Result<usize>
+    where
+        B: AsRef<[u8]>,
+        A: ToSocketAddrs,
+    {
+        let buf = buf.as_ref();
+        let sa = dst
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(addr_not_available)?
+            .into();
+
+        self.0
+            .with(|state, sock| state.group_send(sock, Some(sa), buf))
+            .map(|_: ()| buf.len())
+    }
+}
+
+/// A simulated sequenced packet socket.
+#[derive(Debug)]
+pub struct SeqPacket(Handle);
+
+impl SeqPacket {
+    /// Binds this socket to the specified address.
+    pub fn bind<A: ToServiceRanges>(&self, addr: A) -> io::Result<()> {
+        self.0.with(|state, sock| state.bind(sock, addr))
+    }
+
+    /// Listens for the incoming connections.
+    pub fn listen(&self) -> io::Result<()> {
+        self.0.with(|state, sock| {
+            let port = state.port_mut(sock)?;
+
+            match port.conn {
+                Conn::Idle => {
+                    port.conn = Conn::Listening(VecDeque::new());
+
+                    Ok(())
+                }
+                Conn::Listening(_) => Ok(()),
+                _ => Err(io::Error::from_raw_os_error(libc::EINVAL)),
+            }
+        })
+    }
+
+    /// Accepts a new incoming connection from this listener.
+    pub fn accept(&self) -> io::Result<(SeqPacket, SocketAddr)> {
+        let addr = self.0.addr;
+        let accepted = self.0.fabric.wait(addr, false, |state| {
+            let accepted = match state.port_mut(addr)?.conn {
+                Conn::Listening(ref mut backlog) => backlog.pop_front(),
+                _ => return Err(io::Error::from_raw_os_error(libc::EINVAL)),
+            };
+
+            Ok(accepted.map(|accepted| match state.socks[&accepted].conn {
+                Conn::Connected(peer) => (accepted, peer),
+                _ => (accepted, SocketAddr::default()),
+            }))
+        })?;
+
+        Ok((
+            SeqPacket(Handle {
+                fabric: self.0.fabric.clone(),
+                addr: accepted.0,
+            }),
+            accepted.1,
+        ))
+    }
+
+    /// Opens a connection to the listener bound to the service address.
+    pub fn connect<A: ToServiceAddrs>(&self, addr: A) -> io::Result<()> {
+        self.0.with(|state, sock| state.connect(sock, addr))
+    }
+
+    /// Moves this socket into or out of nonblocking mode.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.0.set_nonblocking(nonblocking)
+    }
+
+    /// Sets the read timeout, the receive calls fail with `WouldBlock` after the timeout.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.0.set_read_timeout(timeout)
+    }
+
+    /// Returns the address of the local half of this socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.local_addr()
+    }
+
+    /// Returns the socket address of the remote peer of this connection.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.0.with(|state, sock| match state.port(sock)?.conn {
+            Conn::Connected(peer) => Ok(peer),
+            _ => Err(io::ErrorKind::NotConnected.into()),
+        })
+    }
+
+    /// Sends data on the connection.
+    pub fn send<B: AsRef<[u8]>>(&self, buf: B) -> io::Result<usize> {
+        let buf = buf.as_ref();
+
+        self.0.with(|state, sock| match state.port(sock)?.conn {
+            Conn::Connected(peer) => {
+                state.deliver(sock, peer, None, buf.to_vec());
+
+                Ok(buf.len())
+            }
+            Conn::Disconnected(_) => Err(io::ErrorKind::BrokenPipe.into()),
+            _ => Err(io::ErrorKind::NotConnected.into()),
+        })
+    }
+
+    /// Receives data from the connection.
+    ///
+    /// Returns zero if the peer has shut down the connection,
+    /// an error of the kind mapped from the `ErrorCode` if the connection was aborted,
+    /// or `InvalidData` with `Truncated` if the message didn't fit the buffer.
+    pub fn recv<B: AsMut<[u8]>>(&self, mut buf: B) -> io::Result<usize> {
+        match self.recv_msg(buf.as_mut(), Recv::empty())? {
+            (RecvMsg::Message { len, truncated, .. }, _) => {
+                Truncated::check(len, truncated, Recv::empty())
+            }
+            (RecvMsg::Rejected(rejected), _) => rejected.err.into_conn_error().map_or(Ok(0), Err),
+            _ => Err(io::ErrorKind::ConnectionReset.into()),
+        }
+    }
+
+    /// Returns the length of the next message, without removing it from the queue.
+    ///
+    /// The length of the returned data is reported if the connection was aborted.
+    pub fn next_message_len(&self) -> io::Result<Option<usize>> {
+        let addr = self.0.addr;
+
+        self.0.fabric.wait(addr, false, |state| {
+            match state.next_message_len(addr)? {
+                Some(len) => Ok(Some(len)),
+                // the reason why the connection was aborted is received
+                None => match state.port(addr)?.conn {
+                    Conn::Disconnected(_) => Ok(Some(Some(0))),
+                    _ => Ok(None),
+                },
+            }
+        })
+    }
+
+    /// Receives a message or the reason why the connection was aborted.
+    pub fn recv_msg<B: AsMut<[u8]>>(
+        &self,
+        mut buf: B,
+        flags: Recv,
+    ) -> io::Result<(RecvMsg, SocketAddr)> {
+        let buf = buf.as_mut();
+        let addr = self.0.addr;
+
+        self.0
+            .fabric
+            .wait(addr, flags.contains(Recv::DONT_WAIT), |state| {
+                match state.recv_msg(addr, buf, flags)? {
+                    Some(res) => Ok(Some(res)),
+                    None => match state.port(addr)?.conn {
+                        Conn::Disconnected(peer) => Ok(Some((
+                            RecvMsg::Rejected(Rejected {
+                                err: ErrorCode::ConnShutdown,
+                                len: 0,
+                                truncated: false,
+                                dest: Destination::Socket(peer),
+                            }),
+                            addr,
+                        ))),
+                        _ => Ok(None),
+                    },
+                }
+            })
+    }
+
+    /// Shuts down the connection.
+    pub fn shutdown(&self) -> io::Result<()> {
+        self.0.with(|state, sock| match state.port(sock)?.conn {
+            Conn::Connected(peer) => {
+                state.port_mut(sock)?.conn = Conn::Disconnected(peer);
+                state.disconnect(peer, sock, ffi::TIPC_CONN_SHUTDOWN);
+
+                Ok(())
+            }
+            _ => Err(io::ErrorKind::NotConnected.into()),
+        })
+    }
+}
+
+/// A simulated topology server.
+#[derive(Debug)]
+pub struct Server(Handle);
+
+impl Server {
+    /// Returns the address of the local half of this socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.local_addr()
+    }
+
+    /// Moves this socket into or out of nonblocking mode.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.0.set_nonblocking(nonblocking)
+    }
+
+    /// The subscriber wants `All` or `Edge` event for each matching update of the binding table.
+    pub fn subscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<Subscription> {
+        let sub = sub.into();
+
+        self.0
+            .with(|state, sock| state.subscribe(sock, sub))
+            .map(|_: ()| sub)
+    }
+
+    /// The subscriber doesn't want any more events for this service range.
+    pub fn unsubscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<()> {
+        let sub = sub.into();
+
+        self.0.with(|state, sock| {
+            state.port_mut(sock)?.subs.retain(|&(s, _)| s != sub);
+
+            Ok(())
+        })
+    }
+
+    /// Receives events for the subscriptions.
+    ///
+    /// An expired subscription is reported as the `Expired` inner error of a `TimedOut` error.
+    pub fn recv(&self) -> io::Result<Event> {
+        self.recv_event()?.ok_or_else(bad_socket)
+    }
+
+    /// Receives events for the subscriptions,
+    /// returns `None` if the socket is gone with its killed node.
+    pub(crate) fn recv_event(&self) -> io::Result<Option<Event>> {
+        let addr = self.0.addr;
+
+        self.0.fabric.wait(addr, false, |state| {
+            let port = match state.socks.get_mut(&addr) {
+                Some(port) => port,
+                None => return Ok(Some(None)),
+            };
+
+            if let Some(Packet::Event(event)) = port.queue.pop_front() {
+                return Ok(Some(Some(event)));
+            }
+
+            let now = Instant::now();
+
+            if let Some(idx) = port
+                .subs
+                .iter()
+                .position(|&(_, deadline)| deadline.is_some_and(|deadline| deadline <= now))
+            {
+                let (sub, _) = port.subs.remove(idx);
+
+                return Err(io::Error::new(io::ErrorKind::TimedOut, Expired(sub)));
+            }
+
+            Ok(None)
+        })
+    }
+}
+
+/// An iterator over the events, ends when the socket is gone with its killed node.
+#[derive(Debug)]
+pub struct Events<'a>(&'a Server);
+
+impl<'a> Iterator for Events<'a> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<io::Result<Event>> {
+        self.0.recv_event().transpose()
+    }
+}
+
+impl<'a> IntoIterator for &'a Server {
+    type Item = io::Result<Event>;
+    type IntoIter = Events<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Events(self)
+    }
+}
+
+/// An iterator over the node events, ends when the socket is gone with its killed node.
+#[derive(Debug)]
+pub struct Nodes(Server);
+
+impl Iterator for Nodes {
+    type Item = io::Result<topo::Node>;
+
+    fn next(&mut self) -> Option<io::Result<topo::Node>> {
+        self.0
+            .recv_event()
+            .map(|evt| evt.map(topo::Node::from))
+            .transpose()
+    }
+}
+
+impl Nodes {
+    pub fn recv(&self) -> io::Result<topo::Node> {
+        self.0.recv().map(topo::Node::from)
+    }
+
+    /// Moves this socket into or out of nonblocking mode.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.0.set_nonblocking(nonblocking)
+    }
+}
+
+impl Inject for Fabric {
+    type Node = Instance;
+
+    fn inject(&self, fault: Fault<Instance>) -> io::Result<()> {
+        self.with(|state| {
+            match fault {
+                Fault::Impair { node, peer, .. }
+                | Fault::BearerDown { node, peer }
+                | Fault::BearerUp { node, peer } => {
+                    if !state.net.nodes.contains(&node) || !state.net.nodes.contains(&peer) {
+                        return Err(io::Error::new(io::ErrorKind::NotFound, "node not found"));
+                    }
+                }
+                Fault::Kill(node) => {
+                    if !state.net.nodes.contains(&node) {
+                        return Err(io::Error::new(io::ErrorKind::NotFound, "node not found"));
+                    }
+                }
+            }
+
+            match fault {
+                Fault::Impair {
+                    node,
+                    peer,
+                    impairment,
+                } => {
+                    impairment.validate()?;
+
+                    if impairment.is_empty() {
+                        state.impairments.remove(&(node, peer));
+                    } else {
+                        state.impairments.insert((node, peer), impairment);
+                    }
+                }
+                Fault::BearerDown { node, peer } => state.change(|state| {
+                    state.net.down.insert((node.min(peer), node.max(peer)));
+                }),
+                Fault::BearerUp { node, peer } => state.change(|state| {
+                    state.net.down.remove(&(node.min(peer), node.max(peer)));
+                }),
+                Fault::Kill(node) => state.kill(node),
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl transport::MessageSocket for Datagram {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Datagram::local_addr(self)
+    }
+
+    fn send_to<B, A>(&self, buf: B, dst: A) -> io::Result<usize>
+    where
+        B: AsRef<[u8]>,
+        A: ToSocketAddrs,
+    {
+        Datagram::send_to(self, buf, dst)
+    }
+
+    fn recv_from<B: AsMut<[u8]>>(&self, buf: B) -> io::Result<(usize, SocketAddr)> {
+        Datagram::recv_from(self, buf)
+    }
+
+    fn recv_msg<B: AsMut<[u8]>>(&self, buf: B, flags: Recv) -> io::Result<(RecvMsg, SocketAddr)> {
+        Datagram::recv_msg(self, buf, flags)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        Datagram::set_read_timeout(self, timeout)
+    }
+}
+
+impl transport::ConnectedSocket for SeqPacket {
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        SeqPacket::peer_addr(self)
+    }
+
+    fn send<B: AsRef<[u8]>>(&self, buf: B) -> io::Result<usize> {
+        SeqPacket::send(self, buf)
+    }
+
+    fn recv<B: AsMut<[u8]>>(&self, buf: B) -> io::Result<usize> {
+        SeqPacket::recv(self, buf)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        SeqPacket::shutdown(self)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        SeqPacket::set_read_timeout(self, timeout)
+    }
+}
+
+impl transport::SeqPacketSocket for SeqPacket {}
+
+impl transport::TopologySource for Server {
+    fn subscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<Subscription> {
+        Server::subscribe(self, sub)
+    }
+
+    fn unsubscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<()> {
+        Server::unsubscribe(self, sub)
+    }
+
+    fn recv(&self) -> io::Result<Event> {
+        Server::recv(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fault::Script, transport::MessageSocket};
+
+    use super::*;
+
+    const SERVICE: ServiceAddr = ServiceAddr::new(18888, 17);
+
+    #[test]
+    fn anycast() {
+        let fabric = Fabric::new();
+        let node = fabric.node(1).unwrap();
+        let server = node.rdm().unwrap();
+        let client = node.rdm().unwrap();
+        let mut buf = [0; 16];
+
+        let addr = server.local_addr().unwrap();
+
+        server.bind(SERVICE).unwrap();
+        client.send_to(b"hello", SERVICE).unwrap();
+
+        let (len, from) = server.recv_from(&mut buf[..]).unwrap();
+
+        assert_eq!(&buf[..len], b"hello");
+        assert_eq!(from, client.local_addr().unwrap());
+
+        server.unbind(SERVICE).unwrap();
+
+        assert_eq!(
+            client
+                .send_to(b"hello", SERVICE)
+                .unwrap_err()
+                .raw_os_error(),
+            Some(libc::EHOSTUNREACH)
+        );
+
+        client.set_rejectable(true).unwrap();
+        drop(server);
+        client.send_to(&[0xAA; 32][..], addr).unwrap();
+
+        match client.recv_msg(&mut buf[..], Recv::DONT_WAIT).unwrap() {
+            (RecvMsg::Rejected(rejected), _) => assert_eq!(
+                rejected,
+                Rejected {
+                    err: ErrorCode::NoPort,
+                    len: 32,
+                    truncated: true,
+                    dest: Destination::Socket(addr),
+                }
+            ),
+            res => panic!("unexpected {:?}", res),
+        }
+
+        assert_eq!(buf, [0xAA; 16]);
+    }
+
+    #[test]
+    fn connection() {
+        let fabric = Fabric::new();
+        let listener = fabric.node(1).unwrap().seq_packet().unwrap();
+        let client = fabric.node(2).unwrap().seq_packet().unwrap();
+        let mut buf = [0; 16];
+
+        listener.bind(SERVICE).unwrap();
+        listener.listen().unwrap();
+        client.connect(SERVICE).unwrap();
+
+        let (conn, peer) = listener.accept().unwrap();
+
+        assert_eq!(peer, client.local_addr().unwrap());
+        assert_eq!(client.peer_addr().unwrap(), conn.local_addr().unwrap());
+
+        client.send(b"ping").unwrap();
+        assert_eq!(conn.recv(&mut buf[..]).unwrap(), 4);
+
+        client.send(&[0; 32][..]).unwrap();
+        assert_eq!(conn.next_message_len().unwrap(), Some(32));
+
+        let err = conn.recv(&mut buf[..]).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<Truncated>(),
+            Some(&Truncated { len: 16 })
+        );
+
+        client.shutdown().unwrap();
+        assert_eq!(conn.recv(&mut buf[..]).unwrap(), 0);
+        assert_eq!(
+            conn.send(b"pong").unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+    }
+
+    #[test]
+    fn topology() {
+        let fabric = Fabric::new();
+        let node = fabric.node(1).unwrap();
+        let nodes = node.neighbor_nodes().unwrap();
+
+        assert_eq!(nodes.recv().unwrap().instance(), 1);
+
+        fabric.node(2).unwrap();
+
+        assert_eq!(nodes.recv().unwrap().instance(), 2);
+
+        let srv = node.topology().unwrap();
+        let sock = node.rdm().unwrap();
+
+        srv.subscribe(ServiceRange::from(SERVICE)).unwrap();
+        sock.bind(SERVICE).unwrap();
+
+        let evt = srv.recv().unwrap();
+
+        assert!(evt.available());
+        assert_eq!(evt.sock(), sock.local_addr().unwrap());
+
+        drop(sock);
+
+        assert!(!srv.recv().unwrap().available());
+
+        srv.subscribe(Subscription::from(SERVICE).timeout(Duration::from_millis(10)))
+            .unwrap();
+
+        let err = srv.recv().unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn group() {
+        let fabric = Fabric::new();
+        let node = fabric.node(1).unwrap();
+        let first = node.rdm().unwrap();
+        let second = node.rdm().unwrap();
+        let mut buf = [0; 16];
+
+        first
+            .join(ServiceAddr::new(18888, 1), Join::MEMBER_EVTS)
+            .unwrap();
+        second
+            .join(ServiceAddr::new(18888, 2), Join::MEMBER_EVTS)
+            .unwrap();
+
+        assert_eq!(first.next_message_len().unwrap(), None);
+
+        match first.recv_msg(&mut buf[..], Recv::DONT_WAIT).unwrap() {
+            (RecvMsg::MemberJoin(member), sock) => {
+                assert_eq!(member, ServiceAddr::new(18888, 2));
+                assert_eq!(sock, second.local_addr().unwrap());
+            }
+            res => panic!("unexpected {:?}", res),
+        }
+
+        second.broadcast(b"hello").unwrap();
+
+        assert_eq!(first.next_message_len().unwrap(), Some(5));
+        assert_eq!(first.recv_from(&mut buf[..]).unwrap().0, 5);
+
+        second.leave().unwrap();
+
+        match first.recv_msg(&mut buf[..], Recv::DONT_WAIT).unwrap() {
+            (RecvMsg::MemberLeave(member), _) => assert_eq!(member, ServiceAddr::new(18888, 2)),
+            res => panic!("unexpected {:?}", res),
+        }
+    }
+
+    fn echo<S: MessageSocket>(sock: &S) -> io::Result<usize> {
+        let mut buf = [0; 64];
+        let (len, from) = sock.recv_from(&mut buf[..])?;
+
+        sock.send_to(&buf[..len], from)
+    }
+
+    #[test]
+    fn transport() {
+        let fabric = Fabric::new();
+        let server = fabric.node(1).unwrap().rdm().unwrap();
+        let client = fabric.node(2).unwrap().rdm().unwrap();
+        let mut buf = [0; 16];
+
+        server.bind(SERVICE).unwrap();
+        client.send_to(b"ping", SERVICE).unwrap();
+
+        assert_eq!(echo(&server).unwrap(), 4);
+        assert_eq!(
+            client.recv_from(&mut buf[..]).unwrap(),
+            (4, server.local_addr().unwrap())
+        );
+    }
+
+    #[test]
+    fn faults() {
+        let fabric = Fabric::new();
+        let node = fabric.node(1).unwrap();
+        let mut nodes = node.neighbor_nodes().unwrap();
+        let client = node.rdm().unwrap();
+        let server = fabric.node(2).unwrap().rdm().unwrap();
+        let mut buf = [0; 16];
+
+        assert!(nodes.recv().unwrap().available());
+        assert!(nodes.recv().unwrap().available());
+
+        server.bind(SERVICE).unwrap();
+        client.set_rejectable(true).unwrap();
+
+        fabric
+            .inject(Fault::Impair {
+                node: 1,
+                peer: 2,
+                impairment: Impairment::default().delay(Duration::from_millis(20)),
+            })
+            .unwrap();
+        client.send_to(b"hello", SERVICE).unwrap();
+        fabric.inject(Fault::Kill(2)).unwrap();
+
+        let evt = nodes.recv().unwrap();
+
+        assert!(!evt.available());
+        assert_eq!(evt.instance(), 2);
+
+        match client.recv_msg(&mut buf[..], Recv::empty()).unwrap() {
+            (RecvMsg::Rejected(rejected), _) => assert_eq!(rejected.err, ErrorCode::NoNode),
+            res => panic!("unexpected {:?}", res),
+        }
+
+        assert_eq!(
+            server.local_addr().unwrap_err().raw_os_error(),
+            Some(libc::EBADF)
+        );
+
+        fabric.inject(Fault::Kill(1)).unwrap();
+
+        assert!(nodes.next().is_none());
+    }
+
+    #[test]
+    fn script() {
+        let fabric = Fabric::new();
+        let listener = fabric.node(1).unwrap().seq_packet().unwrap();
+        let client = fabric.node(2).unwrap().seq_packet().unwrap();
+        let mut buf = [0; 16];
+
+        listener.bind(SERVICE).unwrap();
+        listener.listen().unwrap();
+        client.connect(SERVICE).unwrap();
+
+        let (conn, _) = listener.accept().unwrap();
+
+        Script::new()
+            .at(
+                Duration::from_millis(10),
+                Fault::BearerDown { node: 2, peer: 1 },
+            )
+            .at(Duration::default(), Fault::BearerUp { node: 1, peer: 2 })
+            .run(&fabric)
+            .unwrap();
+
+        match conn.recv_msg(&mut buf[..], Recv::empty()).unwrap() {
+            (RecvMsg::Rejected(rejected), _) => assert_eq!(rejected.err, ErrorCode::NoNode),
+            res => panic!("unexpected {:?}", res),
+        }
+
+        let err = client.recv(&mut buf[..]).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+        assert_eq!(ErrorCode::from_io_error(&err), Some(ErrorCode::NoNode));
+        assert_eq!(
+            client.send(b"hello").unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+    }
+}
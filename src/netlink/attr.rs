@@ -0,0 +1,314 @@
+//! Netlink message and attribute encoding.
+
+use core::convert::TryInto;
+use core::iter;
+use core::mem;
+use core::str;
+
+use std::io;
+
+const NLMSG_HDRLEN: usize = mem::size_of::<libc::nlmsghdr>();
+const GENL_HDRLEN: usize = mem::size_of::<libc::genlmsghdr>();
+const NLA_HDRLEN: usize = mem::size_of::<libc::nlattr>();
+
+/// Rounds the length up to the netlink alignment.
+pub const fn align(len: usize) -> usize {
+    (len + libc::NLA_ALIGNTO as usize - 1) & !(libc::NLA_ALIGNTO as usize - 1)
+}
+
+pub fn invalid_data<S: Into<String>>(msg: S) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// A generic netlink request message.
+#[derive(Clone, Debug)]
+pub struct Message {
+    buf: Vec<u8>,
+}
+
+impl Message {
+    /// Constructs a new message of the given family and command.
+    pub fn new(family: u16, flags: u16, cmd: u8, version: u8) -> Self {
+        let mut hdr = [0; GENL_HDRLEN];
+
+        hdr[0] = cmd;
+        hdr[1] = version;
+
+        Self::with_header(family, flags, &hdr)
+    }
+
+    /// Constructs a new message of the given type with the family specific header.
+    pub fn with_header(ty: u16, flags: u16, hdr: &[u8]) -> Self {
+        let mut buf = vec![0; NLMSG_HDRLEN];
+
+        buf[4..6].copy_from_slice(&ty.to_ne_bytes());
+        buf[6..8].copy_from_slice(&(flags | libc::NLM_F_REQUEST as u16).to_ne_bytes());
+        buf.extend_from_slice(hdr);
+        buf.resize(align(buf.len()), 0);
+
+        Message { buf }
+    }
+
+    /// Reserves the capacity for the attributes of the given length,
+    /// the buffer isn't reallocated until they are appended.
+    pub fn reserve(&mut self, len: usize) -> &mut Self {
+        self.buf.reserve_exact(len);
+        self
+    }
+
+    /// Finishes the message with the sequence number.
+    pub fn finish(mut self, seq: u32) -> Vec<u8> {
+        let len = self.buf.len() as u32;
+
+        self.buf[0..4].copy_from_slice(&len.to_ne_bytes());
+        self.buf[8..12].copy_from_slice(&seq.to_ne_bytes());
+        self.buf
+    }
+
+    /// Appends an attribute with the raw payload.
+    pub fn put_bytes(&mut self, ty: u16, data: &[u8]) -> &mut Self {
+        let len = (NLA_HDRLEN + data.len()) as u16;
+
+        self.buf.extend_from_slice(&len.to_ne_bytes());
+        self.buf.extend_from_slice(&ty.to_ne_bytes());
+        self.buf.extend_from_slice(data);
+        self.buf.resize(align(self.buf.len()), 0);
+        self
+    }
+
+    /// Appends a flag attribute.
+    pub fn put_flag(&mut self, ty: u16) -> &mut Self {
+        self.put_bytes(ty, &[])
+    }
+
+    /// Appends an `u32` attribute.
+    pub fn put_u32(&mut self, ty: u16, n: u32) -> &mut Self {
+        self.put_bytes(ty, &n.to_ne_bytes())
+    }
+
+    /// Appends a NUL terminated string attribute.
+    pub fn put_str(&mut self, ty: u16, s: &str) -> &mut Self {
+        let mut data = Vec::with_capacity(s.len() + 1);
+
+        data.extend_from_slice(s.as_bytes());
+        data.push(0);
+
+        self.put_bytes(ty, &data)
+    }
+
+    /// Appends a nested attribute which is filled by the closure.
+    pub fn nest<F>(&mut self, ty: u16, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut Self),
+    {
+        let off = self.buf.len();
+
+        self.put_bytes(ty | libc::NLA_F_NESTED as u16, &[]);
+
+        f(self);
+
+        let len = (self.buf.len() - off) as u16;
+
+        self.buf[off..off + 2].copy_from_slice(&len.to_ne_bytes());
+        self
+    }
+}
+
+/// A netlink message received from the kernel.
+#[derive(Clone, Copy, Debug)]
+pub struct Reply<'a> {
+    /// The message type.
+    pub ty: u16,
+    /// The message sequence number.
+    pub seq: u32,
+    /// The message payload.
+    pub payload: &'a [u8],
+}
+
+impl<'a> Reply<'a> {
+    /// The attributes of a generic netlink message.
+    pub fn attrs(&self) -> Attrs<'a> {
+        Attrs::new(self.payload.get(GENL_HDRLEN..).unwrap_or_default())
+    }
+}
+
+/// Splits the received buffer into netlink messages.
+pub fn replies(mut buf: &[u8]) -> impl Iterator<Item = io::Result<Reply<'_>>> {
+    iter::from_fn(move || {
+        if buf.len() < NLMSG_HDRLEN {
+            return None;
+        }
+
+        let len = u32::from_ne_bytes(buf[0..4].try_into().unwrap()) as usize;
+
+        if len < NLMSG_HDRLEN || len > buf.len() {
+            buf = &[];
+
+            return Some(Err(invalid_data("truncated netlink message")));
+        }
+
+        let reply = Reply {
+            ty: u16::from_ne_bytes(buf[4..6].try_into().unwrap()),
+            seq: u32::from_ne_bytes(buf[8..12].try_into().unwrap()),
+            payload: &buf[NLMSG_HDRLEN..len],
+        };
+
+        buf = buf.get(align(len)..).unwrap_or_default();
+
+        Some(Ok(reply))
+    })
+}
+
+/// An iterator over the netlink attributes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Attrs<'a>(&'a [u8]);
+
+impl<'a> Attrs<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Attrs(buf)
+    }
+
+    /// Finds the first attribute of the type.
+    pub fn get(self, ty: u16) -> Option<Attr<'a>> {
+        self.into_iter().find(|attr| attr.ty == ty)
+    }
+
+    /// Finds the first nested attribute of the type.
+    pub fn nested(self, ty: u16) -> Option<Attrs<'a>> {
+        self.get(ty).map(|attr| attr.nested())
+    }
+}
+
+impl<'a> Iterator for Attrs<'a> {
+    type Item = Attr<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let buf = self.0;
+
+        if buf.len() < NLA_HDRLEN {
+            return None;
+        }
+
+        let len = u16::from_ne_bytes(buf[0..2].try_into().unwrap()) as usize;
+        let ty = u16::from_ne_bytes(buf[2..4].try_into().unwrap());
+
+        if len < NLA_HDRLEN || len > buf.len() {
+            self.0 = &[];
+
+            return None;
+        }
+
+        self.0 = buf.get(align(len)..).unwrap_or_default();
+
+        Some(Attr {
+            ty: ty & libc::NLA_TYPE_MASK as u16,
+            data: &buf[NLA_HDRLEN..len],
+        })
+    }
+}
+
+/// A netlink attribute.
+#[derive(Clone, Copy, Debug)]
+pub struct Attr<'a> {
+    /// The attribute type without the nested and byte order flags.
+    pub ty: u16,
+    /// The attribute payload.
+    pub data: &'a [u8],
+}
+
+impl<'a> Attr<'a> {
+    pub fn u16(&self) -> io::Result<u16> {
+        self.data
+            .get(..2)
+            .and_then(|b| b.try_into().ok())
+            .map(u16::from_ne_bytes)
+            .ok_or_else(|| invalid_data(format!("invalid u16 attribute {}", self.ty)))
+    }
+
+    pub fn u32(&self) -> io::Result<u32> {
+        self.data
+            .get(..4)
+            .and_then(|b| b.try_into().ok())
+            .map(u32::from_ne_bytes)
+            .ok_or_else(|| invalid_data(format!("invalid u32 attribute {}", self.ty)))
+    }
+
+    pub fn u64(&self) -> io::Result<u64> {
+        self.data
+            .get(..8)
+            .and_then(|b| b.try_into().ok())
+            .map(u64::from_ne_bytes)
+            .ok_or_else(|| invalid_data(format!("invalid u64 attribute {}", self.ty)))
+    }
+
+    /// The string payload without the NUL terminator.
+    pub fn str(&self) -> io::Result<&'a str> {
+        let data = self.data;
+        let data = data
+            .iter()
+            .position(|&b| b == 0)
+            .map_or(data, |pos| &data[..pos]);
+
+        str::from_utf8(data)
+            .map_err(|_| invalid_data(format!("invalid string attribute {}", self.ty)))
+    }
+
+    /// The nested attributes.
+    pub fn nested(&self) -> Attrs<'a> {
+        Attrs::new(self.data)
+    }
+}
+
+/// A type which can be decoded from the netlink attributes.
+pub trait FromAttrs: Sized {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message() {
+        let mut msg = Message::new(0x1234, libc::NLM_F_ACK as u16, 3, 1);
+
+        msg.nest(1, |msg| {
+            msg.put_str(1, "eth:eth0")
+                .put_u32(3, 0x1001001)
+                .nest(2, |msg| {
+                    msg.put_u32(1, 10);
+                });
+        });
+
+        let buf = msg.finish(42);
+        let reply = replies(&buf).next().unwrap().unwrap();
+
+        assert_eq!(buf.len() % 4, 0);
+        assert_eq!(reply.ty, 0x1234);
+        assert_eq!(
+            u16::from_ne_bytes(buf[6..8].try_into().unwrap()),
+            (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16
+        );
+        assert_eq!(reply.seq, 42);
+        assert_eq!(reply.payload.first(), Some(&3));
+
+        let bearer = reply.attrs().nested(1).unwrap();
+
+        assert_eq!(bearer.get(1).unwrap().str().unwrap(), "eth:eth0");
+        assert_eq!(bearer.get(3).unwrap().u32().unwrap(), 0x1001001);
+        assert_eq!(bearer.nested(2).unwrap().get(1).unwrap().u32().unwrap(), 10);
+        assert!(bearer.get(4).is_none());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut msg = Message::new(0x1234, 0, 3, 1);
+
+        msg.put_u32(1, 1);
+
+        let buf = msg.finish(1);
+
+        assert!(replies(&buf[..buf.len() - 4]).next().unwrap().is_err());
+        assert_eq!(Attrs::new(&buf[20..buf.len() - 4]).count(), 0);
+    }
+}
@@ -0,0 +1,474 @@
+//! Bearer management.
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+
+use std::io;
+use std::net::SocketAddr;
+
+use crate::{addr::NetworkAddr, ffi};
+
+use super::{
+    attr::{Attrs, FromAttrs, Message},
+    raw, Client, Udp,
+};
+
+/// The media type of a bearer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Media {
+    /// Ethernet
+    Eth,
+    /// UDP/IP
+    Udp,
+    /// InfiniBand
+    Ib,
+}
+
+impl Media {
+    /// The media name used as the prefix of bearer names.
+    pub fn name(self) -> &'static str {
+        match self {
+            Media::Eth => "eth",
+            Media::Udp => "udp",
+            Media::Ib => "ib",
+        }
+    }
+}
+
+impl fmt::Display for Media {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Media {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "eth" => Ok(Media::Eth),
+            "udp" => Ok(Media::Udp),
+            "ib" => Ok(Media::Ib),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown media: {}", s),
+            )),
+        }
+    }
+}
+
+/// The link properties of a bearer.
+///
+/// A property which is `None` is left unchanged when it is set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Props {
+    /// The link priority, the link with the highest priority carries the traffic.
+    pub priority: Option<u32>,
+    /// The link tolerance, how long a link may be silent before it is declared down.
+    pub tolerance: Option<Duration>,
+    /// The link window, how many messages may be sent before an acknowledge is required.
+    pub window: Option<u32>,
+    /// The maximum transmission unit, only applicable to the UDP media.
+    pub mtu: Option<u32>,
+}
+
+impl Props {
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    pub fn window(mut self, window: u32) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    pub fn mtu(mut self, mtu: u32) -> Self {
+        self.mtu = Some(mtu);
+        self
+    }
+
+    /// No property will be changed.
+    pub fn is_empty(&self) -> bool {
+        *self == Props::default()
+    }
+
+    /// Checks the properties against the ranges accepted by the kernel.
+    pub fn validate(&self) -> io::Result<()> {
+        fn check(name: &str, value: u32, min: u32, max: u32) -> io::Result<()> {
+            if (min..=max).contains(&value) {
+                Ok(())
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} {} out of range [{}, {}]", name, value, min, max),
+                ))
+            }
+        }
+
+        if let Some(priority) = self.priority {
+            check(
+                "priority",
+                priority,
+                raw::TIPC_MIN_LINK_PRI,
+                raw::TIPC_MAX_LINK_PRI,
+            )?;
+        }
+        if let Some(tolerance) = self.tolerance {
+            let ms = u32::try_from(tolerance.as_millis()).unwrap_or(u32::MAX);
+
+            check(
+                "tolerance",
+                ms,
+                raw::TIPC_MIN_LINK_TOL,
+                raw::TIPC_MAX_LINK_TOL,
+            )?;
+        }
+        if let Some(window) = self.window {
+            check(
+                "window",
+                window,
+                raw::TIPC_MIN_LINK_WIN,
+                raw::TIPC_MAX_LINK_WIN,
+            )?;
+        }
+        if let Some(mtu) = self.mtu {
+            check("mtu", mtu, raw::TIPC_MIN_UDP_MTU, u32::from(u16::MAX))?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn put(&self, msg: &mut Message, ty: u16) {
+        msg.nest(ty, |msg| {
+            if let Some(priority) = self.priority {
+                msg.put_u32(raw::TIPC_NLA_PROP_PRIO, priority);
+            }
+            if let Some(tolerance) = self.tolerance {
+                msg.put_u32(raw::TIPC_NLA_PROP_TOL, tolerance.as_millis() as u32);
+            }
+            if let Some(window) = self.window {
+                msg.put_u32(raw::TIPC_NLA_PROP_WIN, window);
+            }
+            if let Some(mtu) = self.mtu {
+                msg.put_u32(raw::TIPC_NLA_PROP_MTU, mtu);
+            }
+        });
+    }
+}
+
+impl FromAttrs for Props {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let mut props = Props::default();
+
+        for attr in attrs {
+            match attr.ty {
+                raw::TIPC_NLA_PROP_PRIO => props.priority = Some(attr.u32()?),
+                raw::TIPC_NLA_PROP_TOL => {
+                    props.tolerance = Some(Duration::from_millis(u64::from(attr.u32()?)))
+                }
+                raw::TIPC_NLA_PROP_WIN => props.window = Some(attr.u32()?),
+                raw::TIPC_NLA_PROP_MTU => props.mtu = Some(attr.u32()?),
+                _ => {}
+            }
+        }
+
+        Ok(props)
+    }
+}
+
+/// A TIPC bearer, which connects the node to a network interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bearer {
+    /// The bearer name, `<media>:<interface>` such as `eth:eth0`.
+    pub name: String,
+    /// The discovery domain, which limits the nodes the links are established with.
+    ///
+    /// It is only used to enable the bearer, the kernel doesn't report it back.
+    pub domain: Option<NetworkAddr>,
+    /// The link properties of the bearer.
+    pub props: Props,
+    /// The addresses of the UDP bearer.
+    pub udp: Option<Udp>,
+}
+
+impl Bearer {
+    /// Constructs a bearer for the interface of the media.
+    pub fn new(media: Media, ifname: &str) -> Self {
+        Bearer {
+            name: format!("{}:{}", media, ifname),
+            domain: None,
+            props: Props::default(),
+            udp: None,
+        }
+    }
+
+    /// Constructs an Ethernet bearer for the interface.
+    pub fn eth(ifname: &str) -> Self {
+        Self::new(Media::Eth, ifname)
+    }
+
+    /// Constructs an InfiniBand bearer for the interface.
+    pub fn ib(ifname: &str) -> Self {
+        Self::new(Media::Ib, ifname)
+    }
+
+    /// Constructs an UDP bearer with the name, which is bound to the local address
+    /// and discovers the peers with the remote multicast or unicast address.
+    pub fn udp<L, R>(name: &str, local: L, remote: R) -> Self
+    where
+        L: Into<SocketAddr>,
+        R: Into<SocketAddr>,
+    {
+        Self::new(Media::Udp, name).udp_opts(Udp::new(local, remote))
+    }
+
+    /// The media of the bearer.
+    pub fn media(&self) -> io::Result<Media> {
+        self.name.split(':').next().unwrap_or_default().parse()
+    }
+
+    /// The interface name or the UDP bearer name.
+    pub fn ifname(&self) -> &str {
+        self.name.split_once(':').map_or("", |(_, ifname)| ifname)
+    }
+
+    pub fn udp_opts(mut self, udp: Udp) -> Self {
+        self.udp = Some(udp);
+        self
+    }
+
+    pub fn domain<A: Into<NetworkAddr>>(mut self, domain: A) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.props.priority = Some(priority);
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.props.tolerance = Some(tolerance);
+        self
+    }
+
+    pub fn window(mut self, window: u32) -> Self {
+        self.props.window = Some(window);
+        self
+    }
+}
+
+impl FromAttrs for Bearer {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let mut bearer = Bearer {
+            name: String::new(),
+            domain: None,
+            props: Props::default(),
+            udp: None,
+        };
+
+        for attr in attrs {
+            match attr.ty {
+                raw::TIPC_NLA_BEARER_NAME => bearer.name = attr.str()?.to_owned(),
+                raw::TIPC_NLA_BEARER_DOMAIN => bearer.domain = Some(attr.u32()?.into()),
+                raw::TIPC_NLA_BEARER_PROP => bearer.props = Props::from_attrs(attr.nested())?,
+                raw::TIPC_NLA_BEARER_UDP_OPTS => bearer.udp = Some(Udp::from_attrs(attr.nested())?),
+                _ => {}
+            }
+        }
+
+        Ok(bearer)
+    }
+}
+
+pub(crate) fn check_name(name: &str) -> io::Result<()> {
+    if name.is_empty() || name.len() >= ffi::TIPC_MAX_BEARER_NAME as usize {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid bearer name: {}", name),
+        ))
+    } else {
+        name.split(':')
+            .next()
+            .unwrap_or_default()
+            .parse::<Media>()?;
+
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Enables a bearer with the discovery domain and the link properties.
+    ///
+    /// The kernel only accepts the priority when the bearer is enabled,
+    /// the other properties are set afterwards, and the bearer is disabled again if that fails.
+    pub fn enable_bearer(&self, bearer: &Bearer) -> io::Result<()> {
+        check_name(&bearer.name)?;
+        bearer.props.validate()?;
+
+        if bearer.media()? == Media::Udp && bearer.udp.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("missing UDP addresses of bearer {}", bearer.name),
+            ));
+        }
+
+        self.execute(raw::TIPC_NL_BEARER_ENABLE, |msg| {
+            msg.nest(raw::TIPC_NLA_BEARER, |msg| {
+                msg.put_str(raw::TIPC_NLA_BEARER_NAME, &bearer.name);
+
+                if let Some(domain) = bearer.domain {
+                    msg.put_u32(raw::TIPC_NLA_BEARER_DOMAIN, domain.into());
+                }
+                if let Some(priority) = bearer.props.priority {
+                    Props::default()
+                        .priority(priority)
+                        .put(msg, raw::TIPC_NLA_BEARER_PROP);
+                }
+                if let Some(ref udp) = bearer.udp {
+                    udp.put(msg);
+                }
+            });
+        })?;
+
+        // Only the priority is accepted when the bearer is enabled.
+        let props = Props {
+            priority: None,
+            ..bearer.props
+        };
+
+        if props.is_empty() {
+            return Ok(());
+        }
+
+        match self.set_bearer(&bearer.name, props) {
+            Err(err) => {
+                let _ = self.disable_bearer(&bearer.name);
+
+                Err(err)
+            }
+            res => res,
+        }
+    }
+
+    /// Disables the bearer, all the links established over it are reset.
+    pub fn disable_bearer(&self, name: &str) -> io::Result<()> {
+        check_name(name)?;
+
+        self.execute(raw::TIPC_NL_BEARER_DISABLE, |msg| {
+            msg.nest(raw::TIPC_NLA_BEARER, |msg| {
+                msg.put_str(raw::TIPC_NLA_BEARER_NAME, name);
+            });
+        })
+    }
+
+    /// Changes the link properties of the bearer.
+    pub fn set_bearer(&self, name: &str, props: Props) -> io::Result<()> {
+        check_name(name)?;
+        props.validate()?;
+
+        self.execute(raw::TIPC_NL_BEARER_SET, |msg| {
+            msg.nest(raw::TIPC_NLA_BEARER, |msg| {
+                msg.put_str(raw::TIPC_NLA_BEARER_NAME, name);
+                props.put(msg, raw::TIPC_NLA_BEARER_PROP);
+            });
+        })
+    }
+
+    /// Gets the enabled bearer with the name.
+    pub fn bearer(&self, name: &str) -> io::Result<Bearer> {
+        check_name(name)?;
+
+        self.get(raw::TIPC_NL_BEARER_GET, raw::TIPC_NLA_BEARER, |msg| {
+            msg.nest(raw::TIPC_NLA_BEARER, |msg| {
+                msg.put_str(raw::TIPC_NLA_BEARER_NAME, name);
+            });
+        })
+    }
+
+    /// Lists the enabled bearers.
+    pub fn bearers(&self) -> io::Result<Vec<Bearer>> {
+        self.dump(raw::TIPC_NL_BEARER_GET, raw::TIPC_NLA_BEARER, |_| {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        assert!(Props::default().validate().is_ok());
+        assert!(Props::default()
+            .priority(31)
+            .tolerance(Duration::from_millis(50))
+            .window(8191)
+            .mtu(1500)
+            .validate()
+            .is_ok());
+
+        assert!(Props::default().priority(32).validate().is_err());
+        assert!(Props::default()
+            .tolerance(Duration::from_secs(31))
+            .validate()
+            .is_err());
+        assert!(Props::default().window(15).validate().is_err());
+        assert!(Props::default().mtu(127).validate().is_err());
+    }
+
+    /// Requires the root and the `tipc` module.
+    #[cfg(feature = "netns")]
+    #[test]
+    #[ignore]
+    fn bearers() {
+        use crate::{netlink, netns::Cluster};
+
+        let cluster = Cluster::new(2).unwrap();
+        let node = cluster.node(1);
+        let ifname = node.interface(cluster.node(2));
+
+        node.run(|| -> io::Result<()> {
+            let client = netlink::connect()?;
+            let bearer = Bearer::eth(&ifname);
+
+            assert_eq!(client.bearer(&bearer.name)?.media()?, Media::Eth);
+
+            client.set_bearer(&bearer.name, Props::default().priority(20).window(100))?;
+
+            let props = client.bearer(&bearer.name)?.props;
+
+            assert_eq!(props.priority, Some(20));
+            assert_eq!(props.window, Some(100));
+
+            client.disable_bearer(&bearer.name)?;
+
+            assert!(client.bearers()?.iter().all(|b| b.name != bearer.name));
+
+            client.enable_bearer(
+                &bearer
+                    .clone()
+                    .priority(10)
+                    .tolerance(Duration::from_millis(2000)),
+            )?;
+
+            let props = client.bearer(&bearer.name)?.props;
+
+            assert_eq!(props.priority, Some(10));
+            assert_eq!(props.tolerance, Some(Duration::from_millis(2000)));
+
+            assert!(client.enable_bearer(&bearer).is_err());
+
+            Ok(())
+        })
+        .unwrap()
+        .unwrap();
+    }
+}
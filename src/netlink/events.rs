@@ -0,0 +1,319 @@
+//! Configuration change events.
+//!
+//! The TIPC generic netlink family doesn't register any multicast group,
+//! so the changes are detected by comparing the bearers, links and nodes
+//! dumped at a fixed interval, which is driven by a timer file descriptor.
+
+use core::mem;
+use core::ptr;
+use core::time::Duration;
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use crate::{
+    addr::NetworkAddr,
+    sock::{self, IntoResult},
+};
+
+use super::{connect, Bearer, Client, LinkInfo, NodeInfo};
+
+/// A configuration change.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// The bearer was enabled.
+    BearerEnabled(Bearer),
+    /// The bearer was disabled.
+    BearerDisabled(Bearer),
+    /// The properties of the bearer were changed.
+    BearerChanged(Bearer),
+    /// The link was established.
+    LinkAdded(LinkInfo),
+    /// The link was removed.
+    LinkRemoved(LinkInfo),
+    /// The link went up.
+    LinkUp(LinkInfo),
+    /// The link went down.
+    LinkDown(LinkInfo),
+    /// The properties of the link were changed.
+    LinkChanged(LinkInfo),
+    /// The node became reachable.
+    NodeUp(NetworkAddr),
+    /// The node became unreachable or was removed.
+    NodeDown(NetworkAddr),
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    bearers: BTreeMap<String, Bearer>,
+    links: BTreeMap<String, LinkInfo>,
+    nodes: BTreeMap<NetworkAddr, bool>,
+}
+
+impl Snapshot {
+    fn take(client: &Client) -> io::Result<Self> {
+        Ok(Snapshot {
+            bearers: client
+                .bearers()?
+                .into_iter()
+                .map(|bearer| (bearer.name.clone(), bearer))
+                .collect(),
+            links: client
+                .links()?
+                .into_iter()
+                .map(|link| (link.name.clone(), link))
+                .collect(),
+            nodes: client
+                .nodes()?
+                .into_iter()
+                .map(|NodeInfo { addr, up }| (addr, up))
+                .collect(),
+        })
+    }
+
+    /// Appends the changes from the previous snapshot to this one.
+    fn diff(&self, prev: &Snapshot, events: &mut VecDeque<Event>) {
+        for (name, bearer) in &self.bearers {
+            match prev.bearers.get(name) {
+                None => events.push_back(Event::BearerEnabled(bearer.clone())),
+                Some(old) if old.props != bearer.props => {
+                    events.push_back(Event::BearerChanged(bearer.clone()))
+                }
+                _ => {}
+            }
+        }
+        for (name, bearer) in &prev.bearers {
+            if !self.bearers.contains_key(name) {
+                events.push_back(Event::BearerDisabled(bearer.clone()))
+            }
+        }
+
+        for (name, link) in &self.links {
+            match prev.links.get(name) {
+                None => events.push_back(Event::LinkAdded(link.clone())),
+                Some(old) => {
+                    if old.up != link.up {
+                        events.push_back(if link.up {
+                            Event::LinkUp(link.clone())
+                        } else {
+                            Event::LinkDown(link.clone())
+                        });
+                    }
+                    if old.props != link.props
+                        || old.mtu != link.mtu
+                        || old.broadcast_mode != link.broadcast_mode
+                        || old.broadcast_ratio != link.broadcast_ratio
+                    {
+                        events.push_back(Event::LinkChanged(link.clone()));
+                    }
+                }
+            }
+        }
+        for (name, link) in &prev.links {
+            if !self.links.contains_key(name) {
+                events.push_back(Event::LinkRemoved(link.clone()))
+            }
+        }
+
+        for (&addr, &up) in &self.nodes {
+            let was_up = prev.nodes.get(&addr).cloned().unwrap_or_default();
+
+            if up && !was_up {
+                events.push_back(Event::NodeUp(addr));
+            } else if !up && was_up {
+                events.push_back(Event::NodeDown(addr));
+            }
+        }
+        for (&addr, &up) in &prev.nodes {
+            if up && !self.nodes.contains_key(&addr) {
+                events.push_back(Event::NodeDown(addr));
+            }
+        }
+    }
+}
+
+/// A periodic timer.
+#[derive(Debug)]
+struct Timer(RawFd);
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.0);
+        }
+    }
+}
+
+impl Timer {
+    fn new(interval: Duration) -> io::Result<Self> {
+        let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_CLOEXEC) }
+            .into_result()?;
+        let timer = Timer(fd);
+        let ts = libc::timespec {
+            tv_sec: interval.as_secs() as libc::time_t,
+            tv_nsec: interval.subsec_nanos() as libc::c_long,
+        };
+        let spec = libc::itimerspec {
+            it_interval: ts,
+            it_value: ts,
+        };
+
+        unsafe { libc::timerfd_settime(fd, 0, &spec, ptr::null_mut()) }
+            .into_result()
+            .map(|_: ()| timer)
+    }
+
+    /// Waits for the timer expiration.
+    fn wait(&self) -> io::Result<()> {
+        let mut expirations = 0u64;
+
+        unsafe {
+            libc::read(
+                self.0,
+                &mut expirations as *mut _ as *mut _,
+                mem::size_of::<u64>(),
+            )
+        }
+        .into_result()
+        .map(|_: usize| ())
+    }
+}
+
+/// Watches the configuration changes at the interval.
+///
+/// The kernel doesn't multicast the configuration changes, so the configuration is dumped
+/// and compared with the previous one at each interval. Only the net changes are reported,
+/// a transient change between two checks, e.g. a link going down and up again, is lost.
+///
+/// The changes which happened before the watcher was created are not reported.
+pub fn watch(interval: Duration) -> io::Result<Watcher> {
+    if interval == Duration::default() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "zero watch interval",
+        ));
+    }
+
+    let client = connect()?;
+    let snapshot = Snapshot::take(&client)?;
+
+    Ok(Watcher {
+        client,
+        timer: Timer::new(interval)?,
+        snapshot,
+        pending: VecDeque::new(),
+    })
+}
+
+/// An iterator over the configuration change events.
+///
+/// The file descriptor becomes readable when the configuration should be checked again,
+/// so the watcher can be polled with the other sockets in the non-blocking mode.
+/// The iteration ends when there is no more pending event in the non-blocking mode,
+/// it never ends in the blocking mode.
+#[derive(Debug)]
+pub struct Watcher {
+    client: Client,
+    timer: Timer,
+    snapshot: Snapshot,
+    pending: VecDeque<Event>,
+}
+
+impl AsRawFd for Watcher {
+    fn as_raw_fd(&self) -> RawFd {
+        self.timer.0
+    }
+}
+
+impl Iterator for Watcher {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<io::Result<Event>> {
+        match self.recv() {
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => None,
+            res => Some(res),
+        }
+    }
+}
+
+impl Watcher {
+    /// Moves the watcher into or out of non-blocking mode.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        sock::set_nonblocking(self.timer.0, nonblocking)
+    }
+
+    /// Receives the next event, waiting for the changes.
+    ///
+    /// In the non-blocking mode, `WouldBlock` is returned if the configuration
+    /// is not due to be checked and there is no pending event.
+    pub fn recv(&mut self) -> io::Result<Event> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+
+            self.timer.wait()?;
+
+            let snapshot = Snapshot::take(&self.client)?;
+
+            snapshot.diff(&self.snapshot, &mut self.pending);
+
+            self.snapshot = snapshot;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff() {
+        let bearer = Bearer::eth("eth0");
+        let link = LinkInfo {
+            name: "1.1.1:eth0-1.1.2:eth0".to_owned(),
+            ..Default::default()
+        };
+        let node = NetworkAddr::new(1, 1, 2);
+
+        let prev = Snapshot::default();
+        let mut next = Snapshot::default();
+
+        next.bearers.insert(bearer.name.clone(), bearer.clone());
+        next.links.insert(link.name.clone(), link.clone());
+        next.nodes.insert(node, true);
+
+        let mut events = VecDeque::new();
+
+        next.diff(&prev, &mut events);
+
+        assert_eq!(
+            events.drain(..).collect::<Vec<_>>(),
+            vec![
+                Event::BearerEnabled(bearer.clone()),
+                Event::LinkAdded(link.clone()),
+                Event::NodeUp(node),
+            ]
+        );
+
+        let prev = next;
+        let mut next = Snapshot::default();
+        let bearer = bearer.window(100);
+        let link = LinkInfo { up: true, ..link };
+
+        next.bearers.insert(bearer.name.clone(), bearer.clone());
+        next.links.insert(link.name.clone(), link.clone());
+
+        next.diff(&prev, &mut events);
+
+        assert_eq!(
+            events.drain(..).collect::<Vec<_>>(),
+            vec![
+                Event::BearerChanged(bearer),
+                Event::LinkUp(link),
+                Event::NodeDown(node),
+            ]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Encryption key management.
+//!
+//! The kernel only accepts new keys and flushes all of them,
+//! it neither reports the installed keys nor removes a single key.
+
+use core::fmt;
+
+use std::io;
+
+use zeroize::Zeroizing;
+
+use crate::ffi;
+
+use super::{
+    attr::{align, Message},
+    raw, Client, NodeId,
+};
+
+/// The maximum length of the algorithm name.
+const ALG_NAME_LEN: usize = 32;
+
+/// The key is followed by a 32-bit salt.
+const SALT_LEN: usize = 4;
+
+/// The AES-GCM algorithm, the only one supported by the kernel.
+pub const AES_GCM: &str = "gcm(aes)";
+
+/// An AEAD key, the key material is cleared when it is dropped.
+#[derive(Clone)]
+pub struct Key(Zeroizing<Vec<u8>>);
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Key")
+            .field("alg", &self.alg())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl Key {
+    /// Constructs an AES-GCM key, which is a 128, 192 or 256-bit key followed by a 32-bit salt.
+    pub fn new(key: &[u8]) -> io::Result<Self> {
+        Self::with_alg(AES_GCM, key)
+    }
+
+    /// Constructs a key of the algorithm.
+    pub fn with_alg(alg: &str, key: &[u8]) -> io::Result<Self> {
+        if alg.is_empty() || alg.len() >= ALG_NAME_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid algorithm: {}", alg),
+            ));
+        }
+
+        match key.len().checked_sub(SALT_LEN) {
+            Some(16) | Some(24) | Some(32) => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid key length: {}", key.len()),
+                ))
+            }
+        }
+
+        // struct tipc_aead_key { char alg_name[32]; unsigned int keylen; char key[]; }
+        let mut buf = Zeroizing::new(Vec::with_capacity(ALG_NAME_LEN + 4 + key.len()));
+
+        buf.extend_from_slice(alg.as_bytes());
+        buf.resize(ALG_NAME_LEN, 0);
+        buf.extend_from_slice(&(key.len() as u32).to_ne_bytes());
+        buf.extend_from_slice(key);
+
+        Ok(Key(buf))
+    }
+
+    /// The algorithm of the key.
+    pub fn alg(&self) -> &str {
+        let name = &self.0[..ALG_NAME_LEN];
+        let len = name.iter().position(|&b| b == 0).unwrap_or(ALG_NAME_LEN);
+
+        core::str::from_utf8(&name[..len]).unwrap_or_default()
+    }
+
+    /// The length of the key including the salt.
+    pub fn len(&self) -> usize {
+        self.0.len() - ALG_NAME_LEN - 4
+    }
+
+    /// The key is empty, which never happens.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// The length of the attributes of a key request, the nested attribute holds
+/// the node identity, the key and the master flag, each with a 4-byte header.
+fn attrs_len(key: &Key) -> usize {
+    4 + (4 + ffi::TIPC_NODEID_LEN) + (4 + align(key.as_bytes().len())) + 4
+}
+
+/// Appends the key attributes to the request.
+///
+/// The buffer is reserved up front, so that it is never reallocated,
+/// leaving a copy of the key material behind, after the key was appended.
+fn put_key(msg: &mut Message, node: Option<NodeId>, key: &Key, master: bool) {
+    msg.reserve(attrs_len(key));
+    msg.nest(raw::TIPC_NLA_NODE, |msg| {
+        if let Some(node) = node {
+            msg.put_bytes(raw::TIPC_NLA_NODE_ID, node.as_ref());
+        }
+
+        msg.put_bytes(raw::TIPC_NLA_NODE_KEY, key.as_bytes());
+
+        if master {
+            msg.put_flag(raw::TIPC_NLA_NODE_KEY_MASTER);
+        }
+    });
+}
+
+impl Client {
+    /// Installs the key shared by the nodes of the cluster.
+    pub fn set_cluster_key(&self, key: &Key) -> io::Result<()> {
+        self.execute(raw::TIPC_NL_KEY_SET, |msg| put_key(msg, None, key, false))
+    }
+
+    /// Installs the master key, which is used to exchange the session keys between the nodes.
+    pub fn set_master_key(&self, key: &Key) -> io::Result<()> {
+        self.execute(raw::TIPC_NL_KEY_SET, |msg| put_key(msg, None, key, true))
+    }
+
+    /// Installs the key to receive the messages from the peer node.
+    pub fn set_node_key(&self, node: NodeId, key: &Key) -> io::Result<()> {
+        self.execute(raw::TIPC_NL_KEY_SET, |msg| {
+            put_key(msg, Some(node), key, false)
+        })
+    }
+
+    /// Removes all the keys, the encryption is disabled.
+    pub fn flush_keys(&self) -> io::Result<()> {
+        self.execute(raw::TIPC_NL_KEY_FLUSH, |_| {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::netlink;
+
+    const KEY: &[u8] = b"0123456789abcdef0123";
+
+    #[test]
+    fn key() {
+        let key = Key::new(KEY).unwrap();
+
+        assert_eq!(key.alg(), AES_GCM);
+        assert_eq!(key.len(), KEY.len());
+        assert_eq!(&key.as_bytes()[ALG_NAME_LEN + 4..], KEY);
+        assert!(!format!("{:?}", key).contains("0123"));
+
+        assert!(Key::new(&KEY[..16]).is_err());
+        assert!(Key::with_alg("", KEY).is_err());
+    }
+
+    #[test]
+    fn reserved() {
+        let key = Key::new(KEY).unwrap();
+        let mut msg = Message::new(0, 0, 0, 0);
+
+        put_key(&mut msg, Some("node2".parse().unwrap()), &key, true);
+
+        assert!(msg.finish(1).len() <= 20 + attrs_len(&key));
+    }
+
+    /// Runs in a new network namespace, which requires the root and the `tipc` module.
+    ///
+    /// The kernel doesn't report the installed keys, only the requests are checked.
+    #[test]
+    #[ignore]
+    fn set_and_flush() {
+        assert_eq!(unsafe { libc::unshare(libc::CLONE_NEWNET) }, 0);
+
+        let client = netlink::connect().unwrap();
+        let key = Key::new(KEY).unwrap();
+
+        client.set_node_id("node1".parse().unwrap()).unwrap();
+        client.set_cluster_key(&key).unwrap();
+        client.set_master_key(&key).unwrap();
+        client.set_node_key("node2".parse().unwrap(), &key).unwrap();
+        client.flush_keys().unwrap();
+        client.flush_keys().unwrap();
+    }
+}
@@ -0,0 +1,377 @@
+//! Link statistics and properties.
+
+use std::io;
+
+use crate::{addr::NetworkAddr, ffi, topo};
+
+use super::{
+    attr::{invalid_data, Attrs, FromAttrs},
+    raw, Client, Props,
+};
+
+/// The name of the broadcast link.
+pub const BROADCAST_LINK: &str = "broadcast-link";
+
+/// How the multicast messages are sent over the broadcast link.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BroadcastMode {
+    /// Always sent by broadcast.
+    Broadcast,
+    /// Always sent by replicast, a unicast copy to each destination node.
+    Replicast,
+    /// Selected by the ratio of the destination nodes to the cluster nodes.
+    AutoSelect,
+}
+
+impl BroadcastMode {
+    fn from_raw(mode: u32) -> io::Result<Self> {
+        match mode {
+            raw::BCLINK_MODE_BCAST => Ok(BroadcastMode::Broadcast),
+            raw::BCLINK_MODE_RCAST => Ok(BroadcastMode::Replicast),
+            raw::BCLINK_MODE_SEL => Ok(BroadcastMode::AutoSelect),
+            _ => Err(invalid_data(format!("invalid broadcast mode: {}", mode))),
+        }
+    }
+
+    fn into_raw(self) -> u32 {
+        match self {
+            BroadcastMode::Broadcast => raw::BCLINK_MODE_BCAST,
+            BroadcastMode::Replicast => raw::BCLINK_MODE_RCAST,
+            BroadcastMode::AutoSelect => raw::BCLINK_MODE_SEL,
+        }
+    }
+}
+
+/// The statistics of a link.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The received user messages.
+    pub rx_info: u32,
+    /// The received message fragments.
+    pub rx_fragments: u32,
+    /// The received messages which were fragmented.
+    pub rx_fragmented: u32,
+    /// The received bundles.
+    pub rx_bundles: u32,
+    /// The received messages which were bundled.
+    pub rx_bundled: u32,
+    /// The sent user messages.
+    pub tx_info: u32,
+    /// The sent message fragments.
+    pub tx_fragments: u32,
+    /// The sent messages which were fragmented.
+    pub tx_fragmented: u32,
+    /// The sent bundles.
+    pub tx_bundles: u32,
+    /// The sent messages which were bundled.
+    pub tx_bundled: u32,
+    /// The number of the profiled messages.
+    pub msg_prof_total: u32,
+    /// The number of the messages which length was counted.
+    pub msg_len_count: u32,
+    /// The total length of the counted messages.
+    pub msg_len_total: u32,
+    /// The received link state messages.
+    pub rx_states: u32,
+    /// The received link probes.
+    pub rx_probes: u32,
+    /// The received negative acknowledges.
+    pub rx_nacks: u32,
+    /// The received packets which were deferred.
+    pub rx_deferred: u32,
+    /// The sent link state messages.
+    pub tx_states: u32,
+    /// The sent link probes.
+    pub tx_probes: u32,
+    /// The sent negative acknowledges.
+    pub tx_nacks: u32,
+    /// The sent acknowledges.
+    pub tx_acks: u32,
+    /// The retransmitted packets.
+    pub retransmitted: u32,
+    /// The received duplicated packets.
+    pub duplicates: u32,
+    /// How many times the link was congested.
+    pub link_congs: u32,
+    /// The maximum length of the send queue.
+    pub max_queue: u32,
+    /// The average length of the send queue.
+    pub avg_queue: u32,
+}
+
+impl FromAttrs for Stats {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let mut stats = Stats::default();
+
+        for attr in attrs {
+            let value = match attr.ty {
+                raw::TIPC_NLA_STATS_RX_INFO => &mut stats.rx_info,
+                raw::TIPC_NLA_STATS_RX_FRAGMENTS => &mut stats.rx_fragments,
+                raw::TIPC_NLA_STATS_RX_FRAGMENTED => &mut stats.rx_fragmented,
+                raw::TIPC_NLA_STATS_RX_BUNDLES => &mut stats.rx_bundles,
+                raw::TIPC_NLA_STATS_RX_BUNDLED => &mut stats.rx_bundled,
+                raw::TIPC_NLA_STATS_TX_INFO => &mut stats.tx_info,
+                raw::TIPC_NLA_STATS_TX_FRAGMENTS => &mut stats.tx_fragments,
+                raw::TIPC_NLA_STATS_TX_FRAGMENTED => &mut stats.tx_fragmented,
+                raw::TIPC_NLA_STATS_TX_BUNDLES => &mut stats.tx_bundles,
+                raw::TIPC_NLA_STATS_TX_BUNDLED => &mut stats.tx_bundled,
+                raw::TIPC_NLA_STATS_MSG_PROF_TOT => &mut stats.msg_prof_total,
+                raw::TIPC_NLA_STATS_MSG_LEN_CNT => &mut stats.msg_len_count,
+                raw::TIPC_NLA_STATS_MSG_LEN_TOT => &mut stats.msg_len_total,
+                raw::TIPC_NLA_STATS_RX_STATES => &mut stats.rx_states,
+                raw::TIPC_NLA_STATS_RX_PROBES => &mut stats.rx_probes,
+                raw::TIPC_NLA_STATS_RX_NACKS => &mut stats.rx_nacks,
+                raw::TIPC_NLA_STATS_RX_DEFERRED => &mut stats.rx_deferred,
+                raw::TIPC_NLA_STATS_TX_STATES => &mut stats.tx_states,
+                raw::TIPC_NLA_STATS_TX_PROBES => &mut stats.tx_probes,
+                raw::TIPC_NLA_STATS_TX_NACKS => &mut stats.tx_nacks,
+                raw::TIPC_NLA_STATS_TX_ACKS => &mut stats.tx_acks,
+                raw::TIPC_NLA_STATS_RETRANSMITTED => &mut stats.retransmitted,
+                raw::TIPC_NLA_STATS_DUPLICATES => &mut stats.duplicates,
+                raw::TIPC_NLA_STATS_LINK_CONGS => &mut stats.link_congs,
+                raw::TIPC_NLA_STATS_MAX_QUEUE => &mut stats.max_queue,
+                raw::TIPC_NLA_STATS_AVG_QUEUE => &mut stats.avg_queue,
+                _ => continue,
+            };
+
+            *value = attr.u32()?;
+        }
+
+        Ok(stats)
+    }
+}
+
+/// The state, properties and statistics of a link.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkInfo {
+    /// The link name, `<local node>:<local bearer>-<peer node>:<peer bearer>`.
+    pub name: String,
+    /// The address of the peer node.
+    pub dest: NetworkAddr,
+    /// The maximum transmission unit of the link.
+    pub mtu: u32,
+    /// It is the broadcast link.
+    pub broadcast: bool,
+    /// The link is up.
+    pub up: bool,
+    /// The link is active, which carries the traffic to the peer node.
+    pub active: bool,
+    /// The received packets, computed like iproute2 as the next receive sequence number
+    /// less the received user messages.
+    pub rx_packets: u32,
+    /// The sent packets, computed like iproute2 as the next send sequence number
+    /// less the sent user messages.
+    pub tx_packets: u32,
+    /// The link properties.
+    pub props: Props,
+    /// The link statistics.
+    pub stats: Stats,
+    /// The broadcast mode, only reported for the broadcast link.
+    pub broadcast_mode: Option<BroadcastMode>,
+    /// The percentage of the destination nodes from which the broadcast is selected,
+    /// only reported for the broadcast link in the auto selection mode.
+    pub broadcast_ratio: Option<u32>,
+}
+
+impl FromAttrs for LinkInfo {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let mut link = LinkInfo::default();
+        let mut rcv_nxt = 0;
+        let mut snd_nxt = 0;
+
+        for attr in attrs {
+            match attr.ty {
+                raw::TIPC_NLA_LINK_NAME => link.name = attr.str()?.to_owned(),
+                raw::TIPC_NLA_LINK_DEST => link.dest = attr.u32()?.into(),
+                raw::TIPC_NLA_LINK_MTU => link.mtu = attr.u32()?,
+                raw::TIPC_NLA_LINK_BROADCAST => link.broadcast = true,
+                raw::TIPC_NLA_LINK_UP => link.up = true,
+                raw::TIPC_NLA_LINK_ACTIVE => link.active = true,
+                raw::TIPC_NLA_LINK_RX => rcv_nxt = attr.u32()?,
+                raw::TIPC_NLA_LINK_TX => snd_nxt = attr.u32()?,
+                raw::TIPC_NLA_LINK_PROP => {
+                    link.props = Props::from_attrs(attr.nested())?;
+
+                    for attr in attr.nested() {
+                        match attr.ty {
+                            raw::TIPC_NLA_PROP_BROADCAST => {
+                                link.broadcast_mode = Some(BroadcastMode::from_raw(attr.u32()?)?)
+                            }
+                            raw::TIPC_NLA_PROP_BROADCAST_RATIO => {
+                                link.broadcast_ratio = Some(attr.u32()?)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                raw::TIPC_NLA_LINK_STATS => link.stats = Stats::from_attrs(attr.nested())?,
+                _ => {}
+            }
+        }
+
+        // `TIPC_NLA_LINK_RX` and `TIPC_NLA_LINK_TX` are the sequence numbers, not the packet counts.
+        link.rx_packets = rcv_nxt.wrapping_sub(link.stats.rx_info);
+        link.tx_packets = snd_nxt.wrapping_sub(link.stats.tx_info);
+
+        Ok(link)
+    }
+}
+
+fn check_name(name: &str) -> io::Result<()> {
+    if name.is_empty() || name.len() >= ffi::TIPC_MAX_LINK_NAME as usize {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid link name: {}", name),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Gets the link with the name.
+    pub fn link(&self, name: &str) -> io::Result<LinkInfo> {
+        check_name(name)?;
+
+        self.get(raw::TIPC_NL_LINK_GET, raw::TIPC_NLA_LINK, |msg| {
+            msg.nest(raw::TIPC_NLA_LINK, |msg| {
+                msg.put_str(raw::TIPC_NLA_LINK_NAME, name);
+            });
+        })
+    }
+
+    /// Gets the local end of the link which was reported by the topology service.
+    pub fn link_info(&self, link: &topo::Link) -> io::Result<LinkInfo> {
+        self.link(&link.local_link_name()?)
+    }
+
+    /// Lists the links, including the broadcast link.
+    pub fn links(&self) -> io::Result<Vec<LinkInfo>> {
+        self.dump(raw::TIPC_NL_LINK_GET, raw::TIPC_NLA_LINK, |_| {})
+    }
+
+    /// Changes the properties of the link.
+    pub fn set_link(&self, name: &str, props: Props) -> io::Result<()> {
+        check_name(name)?;
+        props.validate()?;
+
+        self.execute(raw::TIPC_NL_LINK_SET, |msg| {
+            msg.nest(raw::TIPC_NLA_LINK, |msg| {
+                msg.put_str(raw::TIPC_NLA_LINK_NAME, name);
+                props.put(msg, raw::TIPC_NLA_LINK_PROP);
+            });
+        })
+    }
+
+    /// Resets the statistics of the link.
+    pub fn reset_link_stats(&self, name: &str) -> io::Result<()> {
+        check_name(name)?;
+
+        self.execute(raw::TIPC_NL_LINK_RESET_STATS, |msg| {
+            msg.nest(raw::TIPC_NLA_LINK, |msg| {
+                msg.put_str(raw::TIPC_NLA_LINK_NAME, name);
+            });
+        })
+    }
+
+    /// Gets the broadcast link, which carries the multicast messages.
+    pub fn broadcast_link(&self) -> io::Result<LinkInfo> {
+        self.link(BROADCAST_LINK)
+    }
+
+    /// Sets how the multicast messages are sent over the broadcast link.
+    pub fn set_broadcast_mode(&self, mode: BroadcastMode) -> io::Result<()> {
+        self.execute(raw::TIPC_NL_LINK_SET, |msg| {
+            msg.nest(raw::TIPC_NLA_LINK, |msg| {
+                msg.put_str(raw::TIPC_NLA_LINK_NAME, BROADCAST_LINK).nest(
+                    raw::TIPC_NLA_LINK_PROP,
+                    |msg| {
+                        msg.put_u32(raw::TIPC_NLA_PROP_BROADCAST, mode.into_raw());
+                    },
+                );
+            });
+        })
+    }
+
+    /// Selects the broadcast automatically, when the destination nodes reach
+    /// the percentage of the cluster nodes, between 1 and 100.
+    pub fn set_broadcast_ratio(&self, ratio: u32) -> io::Result<()> {
+        if !(1..=100).contains(&ratio) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("broadcast ratio {} out of range [1, 100]", ratio),
+            ));
+        }
+
+        self.execute(raw::TIPC_NL_LINK_SET, |msg| {
+            msg.nest(raw::TIPC_NLA_LINK, |msg| {
+                msg.put_str(raw::TIPC_NLA_LINK_NAME, BROADCAST_LINK).nest(
+                    raw::TIPC_NLA_LINK_PROP,
+                    |msg| {
+                        msg.put_u32(
+                            raw::TIPC_NLA_PROP_BROADCAST,
+                            BroadcastMode::AutoSelect.into_raw(),
+                        )
+                        .put_u32(raw::TIPC_NLA_PROP_BROADCAST_RATIO, ratio);
+                    },
+                );
+            });
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::time::Duration;
+
+    use crate::netlink::attr::{replies, Message};
+
+    #[test]
+    fn from_attrs() {
+        let mut msg = Message::new(0x1234, 0, 3, 1);
+
+        msg.nest(raw::TIPC_NLA_LINK, |msg| {
+            msg.put_str(raw::TIPC_NLA_LINK_NAME, "1001001:eth0-1001002:eth0")
+                .put_u32(raw::TIPC_NLA_LINK_DEST, 0x1001002)
+                .put_u32(raw::TIPC_NLA_LINK_MTU, 1500)
+                .put_flag(raw::TIPC_NLA_LINK_UP)
+                .put_flag(raw::TIPC_NLA_LINK_ACTIVE)
+                .put_u32(raw::TIPC_NLA_LINK_RX, 120)
+                .put_u32(raw::TIPC_NLA_LINK_TX, 80)
+                .nest(raw::TIPC_NLA_LINK_PROP, |msg| {
+                    msg.put_u32(raw::TIPC_NLA_PROP_PRIO, 10)
+                        .put_u32(raw::TIPC_NLA_PROP_TOL, 1500)
+                        .put_u32(raw::TIPC_NLA_PROP_WIN, 50);
+                })
+                .nest(raw::TIPC_NLA_LINK_STATS, |msg| {
+                    msg.put_u32(raw::TIPC_NLA_STATS_RX_INFO, 20)
+                        .put_u32(raw::TIPC_NLA_STATS_TX_INFO, 30)
+                        .put_u32(raw::TIPC_NLA_STATS_RETRANSMITTED, 2);
+                });
+        });
+
+        let buf = msg.finish(1);
+        let reply = replies(&buf).next().unwrap().unwrap();
+        let link = LinkInfo::from_attrs(reply.attrs().nested(raw::TIPC_NLA_LINK).unwrap()).unwrap();
+
+        assert_eq!(link.name, "1001001:eth0-1001002:eth0");
+        assert_eq!(u32::from(link.dest), 0x1001002);
+        assert_eq!(link.mtu, 1500);
+        assert!(!link.broadcast);
+        assert!(link.up);
+        assert!(link.active);
+        assert_eq!(link.rx_packets, 100);
+        assert_eq!(link.tx_packets, 50);
+        assert_eq!(
+            link.props,
+            Props::default()
+                .priority(10)
+                .tolerance(Duration::from_millis(1500))
+                .window(50)
+        );
+        assert_eq!(link.stats.retransmitted, 2);
+        assert_eq!(link.broadcast_mode, None);
+    }
+}
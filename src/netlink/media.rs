@@ -0,0 +1,71 @@
+//! Media properties, which are the defaults of the bearers.
+
+use std::io;
+
+use super::{
+    attr::{invalid_data, Attrs, FromAttrs},
+    raw, Client, Media, Props,
+};
+
+/// The properties of a media.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MediaInfo {
+    /// The media type.
+    pub media: Media,
+    /// The default link properties of the bearers over the media.
+    pub props: Props,
+}
+
+impl FromAttrs for MediaInfo {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let media = attrs
+            .get(raw::TIPC_NLA_MEDIA_NAME)
+            .ok_or_else(|| invalid_data("missing media name"))?
+            .str()?
+            .parse()?;
+        let props = attrs
+            .nested(raw::TIPC_NLA_MEDIA_PROP)
+            .map(Props::from_attrs)
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(MediaInfo { media, props })
+    }
+}
+
+impl Client {
+    /// Lists the media supported by the kernel.
+    pub fn media_list(&self) -> io::Result<Vec<MediaInfo>> {
+        self.dump(raw::TIPC_NL_MEDIA_GET, raw::TIPC_NLA_MEDIA, |_| {})
+    }
+
+    /// Gets the properties of the media.
+    pub fn media(&self, media: Media) -> io::Result<MediaInfo> {
+        self.get(raw::TIPC_NL_MEDIA_GET, raw::TIPC_NLA_MEDIA, |msg| {
+            msg.nest(raw::TIPC_NLA_MEDIA, |msg| {
+                msg.put_str(raw::TIPC_NLA_MEDIA_NAME, media.name());
+            });
+        })
+    }
+
+    /// Changes the properties of the media, the MTU is only applicable to the UDP media.
+    ///
+    /// The new properties apply to the bearers enabled afterwards.
+    pub fn set_media(&self, media: Media, props: Props) -> io::Result<()> {
+        props.validate()?;
+
+        if props.mtu.is_some() && media != Media::Udp {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("MTU is not applicable to media {}", media),
+            ));
+        }
+
+        self.execute(raw::TIPC_NL_MEDIA_SET, |msg| {
+            msg.nest(raw::TIPC_NLA_MEDIA, |msg| {
+                msg.put_str(raw::TIPC_NLA_MEDIA_NAME, media.name());
+                props.put(msg, raw::TIPC_NLA_MEDIA_PROP);
+            });
+        })
+    }
+}
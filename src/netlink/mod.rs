@@ -0,0 +1,248 @@
+//! The TIPC configuration service over generic netlink.
+//!
+//! This is the same interface used by the `tipc` tool of iproute2,
+//! the requests changing the configuration require the `CAP_NET_ADMIN` capability.
+
+use core::convert::TryInto;
+use core::mem;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+use zeroize::Zeroize;
+
+use crate::sock::{IntoResult, Socket};
+
+mod attr;
+mod bearer;
+mod events;
+mod key;
+mod link;
+mod media;
+mod monitor;
+mod name_table;
+mod net;
+#[allow(dead_code)]
+mod raw;
+mod socket;
+mod udp;
+
+pub use self::bearer::{Bearer, Media, Props};
+pub use self::events::{watch, Event, Watcher};
+pub use self::key::{Key, AES_GCM};
+pub use self::link::{BroadcastMode, LinkInfo, Stats, BROADCAST_LINK};
+pub use self::media::MediaInfo;
+pub use self::monitor::{Monitor, Peer};
+pub use self::name_table::Publication;
+pub use self::net::{Net, NodeId, NodeInfo};
+pub use self::socket::{Queues, SocketInfo};
+pub use self::udp::{Udp, DEFAULT_UDP_PORT};
+
+use self::attr::{invalid_data, FromAttrs, Message, Reply};
+
+const RECV_BUF_SIZE: usize = 32768;
+
+/// Connects to the TIPC configuration service of the current network namespace.
+pub fn connect() -> io::Result<Client> {
+    let sock = open(libc::NETLINK_GENERIC)?;
+    let mut client = Client {
+        sock,
+        family: libc::GENL_ID_CTRL as u16,
+        seq: AtomicU32::new(1),
+    };
+
+    client.family = client.resolve_family(raw::TIPC_GENL_V2_NAME)?;
+
+    Ok(client)
+}
+
+fn open(protocol: i32) -> io::Result<Socket> {
+    let sd: i32 = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+            protocol,
+        )
+    }
+    .into_result()?;
+
+    Ok(unsafe { Socket::from_raw_fd(sd) })
+}
+
+/// A generic netlink connection to the TIPC configuration service.
+#[derive(Debug)]
+pub struct Client {
+    sock: Socket,
+    family: u16,
+    seq: AtomicU32,
+}
+
+impl AsRawFd for Client {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sock.as_raw_fd()
+    }
+}
+
+impl Client {
+    fn resolve_family(&self, name: &str) -> io::Result<u16> {
+        let mut msg = Message::new(
+            libc::GENL_ID_CTRL as u16,
+            libc::NLM_F_ACK as u16,
+            libc::CTRL_CMD_GETFAMILY as u8,
+            1,
+        );
+
+        msg.put_str(libc::CTRL_ATTR_FAMILY_NAME as u16, name);
+
+        let mut family = None;
+
+        self.transact(msg, |reply| {
+            if let Some(attr) = reply.attrs().get(libc::CTRL_ATTR_FAMILY_ID as u16) {
+                family = Some(attr.u16()?);
+            }
+
+            Ok(())
+        })
+        .map_err(|err| match err.raw_os_error() {
+            Some(libc::ENOENT) => io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("generic netlink family {} not found", name),
+            ),
+            _ => err,
+        })?;
+
+        family.ok_or_else(|| invalid_data("missing family id"))
+    }
+
+    fn message(&self, cmd: u8, flags: i32) -> Message {
+        Message::new(self.family, flags as u16, cmd, raw::TIPC_GENL_V2_VERSION)
+    }
+
+    /// Executes a command which doesn't reply anything.
+    fn execute<F>(&self, cmd: u8, build: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Message),
+    {
+        let mut msg = self.message(cmd, libc::NLM_F_ACK);
+
+        build(&mut msg);
+
+        self.transact(msg, |_| Ok(()))
+    }
+
+    /// Gets the object which is nested in the `nest` attribute of the reply.
+    fn get<T, F>(&self, cmd: u8, nest: u16, build: F) -> io::Result<T>
+    where
+        T: FromAttrs,
+        F: FnOnce(&mut Message),
+    {
+        let mut msg = self.message(cmd, libc::NLM_F_ACK);
+
+        build(&mut msg);
+
+        let mut obj = None;
+
+        self.transact(msg, |reply| {
+            if obj.is_none() {
+                obj = reply.attrs().nested(nest).map(T::from_attrs).transpose()?;
+            }
+
+            Ok(())
+        })?;
+
+        obj.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "object not found"))
+    }
+
+    /// Dumps the objects which are nested in the `nest` attribute of each reply.
+    fn dump<T, F>(&self, cmd: u8, nest: u16, build: F) -> io::Result<Vec<T>>
+    where
+        T: FromAttrs,
+        F: FnOnce(&mut Message),
+    {
+        let mut msg = self.message(cmd, libc::NLM_F_DUMP);
+
+        build(&mut msg);
+
+        let mut objs = vec![];
+
+        self.transact(msg, |reply| {
+            if let Some(attrs) = reply.attrs().nested(nest) {
+                objs.push(T::from_attrs(attrs)?);
+            }
+
+            Ok(())
+        })?;
+
+        Ok(objs)
+    }
+
+    /// Sends the request and handles each reply until it was acknowledged or the dump is done.
+    fn transact<F>(&self, msg: Message, handle: F) -> io::Result<()>
+    where
+        F: FnMut(Reply) -> io::Result<()>,
+    {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+
+        transact(&self.sock, seq, msg, handle)
+    }
+}
+
+/// Sends the request over the netlink socket and handles each reply
+/// until it was acknowledged or the dump is done.
+fn transact<F>(sock: &Socket, seq: u32, msg: Message, mut handle: F) -> io::Result<()>
+where
+    F: FnMut(Reply) -> io::Result<()>,
+{
+    let mut req = msg.finish(seq);
+    let res = unsafe { libc::send(sock.as_raw_fd(), req.as_ptr() as *const _, req.len(), 0) }
+        .into_result();
+
+    // the request may carry the key material
+    req.zeroize();
+    res?;
+
+    let mut buf = vec![0u8; RECV_BUF_SIZE];
+
+    loop {
+        let len = unsafe {
+            libc::recv(
+                sock.as_raw_fd(),
+                buf.as_mut_ptr() as *mut _,
+                buf.len(),
+                libc::MSG_TRUNC,
+            )
+        }
+        .into_result()?;
+
+        if len > buf.len() {
+            return Err(invalid_data("netlink message truncated"));
+        }
+
+        for reply in attr::replies(&buf[..len]) {
+            let reply = reply?;
+
+            if reply.seq != seq {
+                continue;
+            }
+
+            match i32::from(reply.ty) {
+                libc::NLMSG_NOOP => {}
+                libc::NLMSG_ERROR | libc::NLMSG_DONE => {
+                    let errno = reply
+                        .payload
+                        .get(..mem::size_of::<i32>())
+                        .and_then(|b| b.try_into().ok())
+                        .map_or(0, i32::from_ne_bytes);
+
+                    return if errno < 0 {
+                        Err(io::Error::from_raw_os_error(-errno))
+                    } else {
+                        Ok(())
+                    };
+                }
+                _ => handle(reply)?,
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Neighbor monitoring.
+//!
+//! In large clusters each node only supervises the links to a subset of the nodes,
+//! the overlapping rings of the nodes are exchanged to detect the failures of the others.
+
+use core::convert::TryInto;
+
+use std::io;
+
+use crate::{addr::NetworkAddr, sock::BearerId, topo};
+
+use super::{
+    attr::{invalid_data, Attrs, FromAttrs},
+    raw, Client,
+};
+
+/// The neighbor monitor of a bearer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Monitor {
+    /// The bearer identity.
+    pub bearer_id: BearerId,
+    /// The bearer name.
+    pub bearer: String,
+    /// The monitor is activated, the cluster is larger than the activation threshold.
+    pub active: bool,
+    /// The number of the monitored peers.
+    pub peers: u32,
+    /// The generation of the peer list.
+    pub list_gen: u32,
+}
+
+impl FromAttrs for Monitor {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let mut mon = Monitor::default();
+
+        for attr in attrs {
+            match attr.ty {
+                raw::TIPC_NLA_MON_REF => mon.bearer_id = attr.u32()?,
+                raw::TIPC_NLA_MON_ACTIVE => mon.active = true,
+                raw::TIPC_NLA_MON_BEARER_NAME => mon.bearer = attr.str()?.to_owned(),
+                raw::TIPC_NLA_MON_PEERCNT => mon.peers = attr.u32()?,
+                raw::TIPC_NLA_MON_LISTGEN => mon.list_gen = attr.u32()?,
+                _ => {}
+            }
+        }
+
+        Ok(mon)
+    }
+}
+
+/// A peer supervised by the neighbor monitor.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Peer {
+    /// The bearer identity of the monitor.
+    pub bearer_id: BearerId,
+    /// The address of the peer node.
+    pub addr: NetworkAddr,
+    /// The peer is up.
+    pub up: bool,
+    /// The peer is the head of a domain.
+    pub head: bool,
+    /// The peer is in the local domain, the links to it are supervised by this node.
+    pub local: bool,
+    /// The generation of the domain record received from the peer.
+    pub domain_gen: u32,
+    /// The number of the members of the peer domain which were applied.
+    pub applied: u32,
+    /// The up map of the peer domain members.
+    pub up_map: u64,
+    /// The members of the peer domain.
+    pub members: Vec<NetworkAddr>,
+}
+
+impl FromAttrs for Peer {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let mut peer = Peer::default();
+
+        for attr in attrs {
+            match attr.ty {
+                raw::TIPC_NLA_MON_PEER_ADDR => peer.addr = attr.u32()?.into(),
+                raw::TIPC_NLA_MON_PEER_UP => peer.up = true,
+                raw::TIPC_NLA_MON_PEER_HEAD => peer.head = true,
+                raw::TIPC_NLA_MON_PEER_LOCAL => peer.local = true,
+                raw::TIPC_NLA_MON_PEER_DOMGEN => peer.domain_gen = attr.u32()?,
+                raw::TIPC_NLA_MON_PEER_APPLIED => peer.applied = attr.u32()?,
+                raw::TIPC_NLA_MON_PEER_UPMAP => peer.up_map = attr.u64()?,
+                raw::TIPC_NLA_MON_PEER_MEMBERS => {
+                    if attr.data.len() % 4 != 0 {
+                        return Err(invalid_data("invalid domain members"));
+                    }
+
+                    peer.members = attr
+                        .data
+                        .chunks(4)
+                        .map(|b| u32::from_ne_bytes(b.try_into().unwrap()).into())
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        Ok(peer)
+    }
+}
+
+/// The activation threshold of the neighbor monitor.
+struct Threshold(u32);
+
+impl FromAttrs for Threshold {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        attrs
+            .get(raw::TIPC_NLA_MON_ACTIVATION_THRESHOLD)
+            .ok_or_else(|| invalid_data("missing activation threshold"))?
+            .u32()
+            .map(Threshold)
+    }
+}
+
+impl Client {
+    /// Gets the cluster size from which the neighbor monitor is activated.
+    pub fn monitor_threshold(&self) -> io::Result<u32> {
+        self.get(raw::TIPC_NL_MON_GET, raw::TIPC_NLA_MON, |_| {})
+            .map(|Threshold(threshold)| threshold)
+    }
+
+    /// Sets the cluster size from which the neighbor monitor is activated.
+    pub fn set_monitor_threshold(&self, threshold: u32) -> io::Result<()> {
+        self.execute(raw::TIPC_NL_MON_SET, |msg| {
+            msg.nest(raw::TIPC_NLA_MON, |msg| {
+                msg.put_u32(raw::TIPC_NLA_MON_ACTIVATION_THRESHOLD, threshold);
+            });
+        })
+    }
+
+    /// Lists the neighbor monitors of the bearers.
+    pub fn monitors(&self) -> io::Result<Vec<Monitor>> {
+        self.dump(raw::TIPC_NL_MON_GET, raw::TIPC_NLA_MON, |_| {})
+    }
+
+    /// Lists the peers supervised by the neighbor monitor of the bearer.
+    pub fn monitor_peers(&self, bearer_id: BearerId) -> io::Result<Vec<Peer>> {
+        let mut peers: Vec<Peer> =
+            self.dump(raw::TIPC_NL_MON_PEER_GET, raw::TIPC_NLA_MON_PEER, |msg| {
+                msg.nest(raw::TIPC_NLA_MON, |msg| {
+                    msg.put_u32(raw::TIPC_NLA_MON_REF, bearer_id);
+                });
+            })?;
+
+        for peer in &mut peers {
+            peer.bearer_id = bearer_id;
+        }
+
+        Ok(peers)
+    }
+
+    /// Gets the monitor status of the node reported by the topology service on each bearer.
+    pub fn node_monitor(&self, node: &topo::Node) -> io::Result<Vec<Peer>> {
+        let mut peers = vec![];
+
+        for mon in self.monitors()? {
+            peers.extend(
+                self.monitor_peers(mon.bearer_id)?
+                    .into_iter()
+                    .filter(|peer| peer.addr == node.instance()),
+            );
+        }
+
+        Ok(peers)
+    }
+}
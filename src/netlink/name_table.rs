@@ -0,0 +1,92 @@
+//! Name table dump.
+
+use std::io;
+
+use crate::{
+    addr::{NetworkAddr, ServiceRange, SocketAddr, Visibility},
+    ffi,
+};
+
+use super::{
+    attr::{invalid_data, Attrs, FromAttrs},
+    raw, Client,
+};
+
+/// A service binding in the cluster wide name table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Publication {
+    /// The published service range.
+    pub service: ServiceRange,
+    /// The socket which bound the service range.
+    pub sock: SocketAddr,
+    /// The node which published the service range.
+    pub node: NetworkAddr,
+    /// The visibility scope of the binding.
+    pub scope: Visibility,
+    /// The publication key.
+    pub key: u32,
+}
+
+fn visibility(scope: u32) -> io::Result<Visibility> {
+    match scope {
+        ffi::TIPC_ZONE_SCOPE => Ok(Visibility::Zone),
+        ffi::TIPC_CLUSTER_SCOPE => Ok(Visibility::Cluster),
+        ffi::TIPC_NODE_SCOPE => Ok(Visibility::Node),
+        _ => Err(invalid_data(format!("invalid scope: {}", scope))),
+    }
+}
+
+impl FromAttrs for Publication {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let (mut ty, mut lower, mut upper) = (0, 0, 0);
+        let (mut port, mut node) = (0, 0);
+        let mut scope = Visibility::default();
+        let mut key = 0;
+
+        for attr in attrs {
+            match attr.ty {
+                raw::TIPC_NLA_PUBL_TYPE => ty = attr.u32()?,
+                raw::TIPC_NLA_PUBL_LOWER => lower = attr.u32()?,
+                raw::TIPC_NLA_PUBL_UPPER => upper = attr.u32()?,
+                raw::TIPC_NLA_PUBL_SCOPE => scope = visibility(attr.u32()?)?,
+                raw::TIPC_NLA_PUBL_NODE => node = attr.u32()?,
+                raw::TIPC_NLA_PUBL_REF => port = attr.u32()?,
+                raw::TIPC_NLA_PUBL_KEY => key = attr.u32()?,
+                _ => {}
+            }
+        }
+
+        Ok(Publication {
+            service: ServiceRange::new(ty, lower, upper),
+            sock: SocketAddr::new(port, node),
+            node: node.into(),
+            scope,
+            key,
+        })
+    }
+}
+
+/// The name table entry which nests the publication.
+struct Entry(Publication);
+
+impl FromAttrs for Entry {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        attrs
+            .nested(raw::TIPC_NLA_NAME_TABLE_PUBL)
+            .ok_or_else(|| invalid_data("missing publication"))
+            .and_then(Publication::from_attrs)
+            .map(Entry)
+    }
+}
+
+impl Client {
+    /// Lists the publications of the cluster wide name table.
+    pub fn name_table(&self) -> io::Result<Vec<Publication>> {
+        self.dump(
+            raw::TIPC_NL_NAME_TABLE_GET,
+            raw::TIPC_NLA_NAME_TABLE,
+            |_| {},
+        )
+        .map(|entries| entries.into_iter().map(|Entry(publ)| publ).collect())
+    }
+}
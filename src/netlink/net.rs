@@ -0,0 +1,244 @@
+//! Node identity and network configuration.
+
+use core::fmt;
+use core::str::{self, FromStr};
+
+use std::io;
+
+use crate::{addr::NetworkAddr, ffi};
+
+use super::{
+    attr::{Attrs, FromAttrs},
+    raw, Client,
+};
+
+const MIN_NET_ID: u32 = 1;
+const MAX_NET_ID: u32 = 9999;
+
+fn invalid_input<S: Into<String>>(msg: S) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.into())
+}
+
+/// The 128-bit node identity.
+///
+/// The identity is shown as a string if it is printable, otherwise as a hex number,
+/// the same as the `tipc` tool.
+#[derive(Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NodeId(pub [u8; ffi::TIPC_NODEID_LEN]);
+
+impl NodeId {
+    /// The identity is not assigned.
+    pub fn is_unspecified(&self) -> bool {
+        self.0.iter().all(|&b| b == 0)
+    }
+
+    fn as_printable(&self) -> Option<&str> {
+        let len = self.0.iter().position(|&b| b == 0).unwrap_or(self.0.len());
+        let (s, padding) = self.0.split_at(len);
+
+        if len > 0 && s.iter().all(|b| b.is_ascii_graphic()) && padding.iter().all(|&b| b == 0) {
+            str::from_utf8(s).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl From<[u8; ffi::TIPC_NODEID_LEN]> for NodeId {
+    fn from(id: [u8; ffi::TIPC_NODEID_LEN]) -> Self {
+        NodeId(id)
+    }
+}
+
+impl AsRef<[u8]> for NodeId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NodeId({})", self)
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.as_printable() {
+            Some(s) => f.write_str(s),
+            None => self.0.iter().try_for_each(|b| write!(f, "{:02x}", b)),
+        }
+    }
+}
+
+impl FromStr for NodeId {
+    type Err = io::Error;
+
+    /// Parses a string of up to 16 characters, or a hex number of 32 digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut id = [0; ffi::TIPC_NODEID_LEN];
+
+        if s.is_empty() {
+            return Err(invalid_input("empty node identity"));
+        } else if s.len() <= id.len() {
+            id[..s.len()].copy_from_slice(s.as_bytes());
+        } else if s.len() == id.len() * 2 {
+            for (b, digits) in id.iter_mut().zip(s.as_bytes().chunks(2)) {
+                *b = str::from_utf8(digits)
+                    .ok()
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| invalid_input(format!("invalid node identity: {}", s)))?;
+            }
+        } else {
+            return Err(invalid_input(format!("node identity too long: {}", s)));
+        }
+
+        Ok(NodeId(id))
+    }
+}
+
+/// The network configuration of the local node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Net {
+    /// The network identity, only the nodes with the same identity can form a cluster.
+    pub id: u32,
+    /// The node address, which may be hashed from the node identity.
+    pub addr: NetworkAddr,
+    /// The node identity.
+    pub node_id: NodeId,
+}
+
+impl FromAttrs for Net {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let mut net = Net::default();
+
+        for attr in attrs {
+            match attr.ty {
+                raw::TIPC_NLA_NET_ID => net.id = attr.u32()?,
+                raw::TIPC_NLA_NET_ADDR => net.addr = attr.u32()?.into(),
+                raw::TIPC_NLA_NET_NODEID => {
+                    net.node_id.0[..8].copy_from_slice(&attr.u64()?.to_ne_bytes())
+                }
+                raw::TIPC_NLA_NET_NODEID_W1 => {
+                    net.node_id.0[8..].copy_from_slice(&attr.u64()?.to_ne_bytes())
+                }
+                _ => {}
+            }
+        }
+
+        Ok(net)
+    }
+}
+
+/// A node of the cluster.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// The node address.
+    pub addr: NetworkAddr,
+    /// The node is reachable.
+    pub up: bool,
+}
+
+impl FromAttrs for NodeInfo {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let mut node = NodeInfo::default();
+
+        for attr in attrs {
+            match attr.ty {
+                raw::TIPC_NLA_NODE_ADDR => node.addr = attr.u32()?.into(),
+                raw::TIPC_NLA_NODE_UP => node.up = true,
+                _ => {}
+            }
+        }
+
+        Ok(node)
+    }
+}
+
+impl Client {
+    /// Lists the peer nodes which were discovered.
+    pub fn nodes(&self) -> io::Result<Vec<NodeInfo>> {
+        self.dump(raw::TIPC_NL_NODE_GET, raw::TIPC_NLA_NODE, |_| {})
+    }
+
+    /// Gets the network configuration of the local node.
+    pub fn net(&self) -> io::Result<Net> {
+        self.dump(raw::TIPC_NL_NET_GET, raw::TIPC_NLA_NET, |_| {})?
+            .into_iter()
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "network not found"))
+    }
+
+    /// Sets the network identity of the local node, between 1 and 9999.
+    pub fn set_net_id(&self, id: u32) -> io::Result<()> {
+        if !(MIN_NET_ID..=MAX_NET_ID).contains(&id) {
+            return Err(invalid_input(format!("invalid network identity: {}", id)));
+        }
+
+        self.execute(raw::TIPC_NL_NET_SET, |msg| {
+            msg.nest(raw::TIPC_NLA_NET, |msg| {
+                msg.put_u32(raw::TIPC_NLA_NET_ID, id);
+            });
+        })
+    }
+
+    /// Sets the address of the local node.
+    ///
+    /// The address can only be set once, before any bearer is enabled.
+    pub fn set_addr<A: Into<NetworkAddr>>(&self, addr: A) -> io::Result<()> {
+        let addr = addr.into();
+
+        if addr == 0 {
+            return Err(invalid_input("unspecified node address"));
+        }
+
+        self.execute(raw::TIPC_NL_NET_SET, |msg| {
+            msg.nest(raw::TIPC_NLA_NET, |msg| {
+                msg.put_u32(raw::TIPC_NLA_NET_ADDR, addr.into());
+            });
+        })
+    }
+
+    /// Sets the identity of the local node, the node address is hashed from it.
+    ///
+    /// The identity can only be set once, before any bearer is enabled.
+    pub fn set_node_id(&self, id: NodeId) -> io::Result<()> {
+        if id.is_unspecified() {
+            return Err(invalid_input("unspecified node identity"));
+        }
+
+        let (w0, w1) = id.0.split_at(8);
+
+        self.execute(raw::TIPC_NL_NET_SET, |msg| {
+            msg.nest(raw::TIPC_NLA_NET, |msg| {
+                msg.put_bytes(raw::TIPC_NLA_NET_NODEID, w0)
+                    .put_bytes(raw::TIPC_NLA_NET_NODEID_W1, w1);
+            });
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_id() {
+        let id: NodeId = "node1".parse().unwrap();
+
+        assert_eq!(&id.0[..6], b"node1\0");
+        assert_eq!(id.to_string(), "node1");
+
+        let id: NodeId = "0123456789abcdef0123456789abcdef".parse().unwrap();
+
+        assert_eq!(id.0[..2], [0x01, 0x23]);
+        assert_eq!(id.to_string(), "0123456789abcdef0123456789abcdef");
+
+        assert!("".parse::<NodeId>().is_err());
+        assert!("node identity too long".parse::<NodeId>().is_err());
+        assert!("0123456789abcdef0123456789abcdeg"
+            .parse::<NodeId>()
+            .is_err());
+        assert!(NodeId::default().is_unspecified());
+    }
+}
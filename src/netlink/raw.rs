@@ -0,0 +1,212 @@
+/* constants from linux/tipc_netlink.h and linux/tipc_config.h */
+
+pub const TIPC_GENL_V2_NAME: &str = "TIPCv2";
+pub const TIPC_GENL_V2_VERSION: u8 = 1;
+
+pub const TIPC_NL_UNSPEC: u8 = 0;
+pub const TIPC_NL_LEGACY: u8 = 1;
+pub const TIPC_NL_BEARER_DISABLE: u8 = 2;
+pub const TIPC_NL_BEARER_ENABLE: u8 = 3;
+pub const TIPC_NL_BEARER_GET: u8 = 4;
+pub const TIPC_NL_BEARER_SET: u8 = 5;
+pub const TIPC_NL_SOCK_GET: u8 = 6;
+pub const TIPC_NL_PUBL_GET: u8 = 7;
+pub const TIPC_NL_LINK_GET: u8 = 8;
+pub const TIPC_NL_LINK_SET: u8 = 9;
+pub const TIPC_NL_LINK_RESET_STATS: u8 = 10;
+pub const TIPC_NL_MEDIA_GET: u8 = 11;
+pub const TIPC_NL_MEDIA_SET: u8 = 12;
+pub const TIPC_NL_NODE_GET: u8 = 13;
+pub const TIPC_NL_NET_GET: u8 = 14;
+pub const TIPC_NL_NET_SET: u8 = 15;
+pub const TIPC_NL_NAME_TABLE_GET: u8 = 16;
+pub const TIPC_NL_MON_SET: u8 = 17;
+pub const TIPC_NL_MON_GET: u8 = 18;
+pub const TIPC_NL_MON_PEER_GET: u8 = 19;
+pub const TIPC_NL_PEER_REMOVE: u8 = 20;
+pub const TIPC_NL_BEARER_ADD: u8 = 21;
+pub const TIPC_NL_UDP_GET_REMOTEIP: u8 = 22;
+pub const TIPC_NL_KEY_SET: u8 = 23;
+pub const TIPC_NL_KEY_FLUSH: u8 = 24;
+pub const TIPC_NL_ADDR_LEGACY_GET: u8 = 25;
+
+pub const TIPC_NLA_UNSPEC: u16 = 0;
+pub const TIPC_NLA_BEARER: u16 = 1;
+pub const TIPC_NLA_SOCK: u16 = 2;
+pub const TIPC_NLA_PUBL: u16 = 3;
+pub const TIPC_NLA_LINK: u16 = 4;
+pub const TIPC_NLA_MEDIA: u16 = 5;
+pub const TIPC_NLA_NODE: u16 = 6;
+pub const TIPC_NLA_NET: u16 = 7;
+pub const TIPC_NLA_NAME_TABLE: u16 = 8;
+pub const TIPC_NLA_MON: u16 = 9;
+pub const TIPC_NLA_MON_PEER: u16 = 10;
+
+pub const TIPC_NLA_BEARER_UNSPEC: u16 = 0;
+pub const TIPC_NLA_BEARER_NAME: u16 = 1;
+pub const TIPC_NLA_BEARER_PROP: u16 = 2;
+pub const TIPC_NLA_BEARER_DOMAIN: u16 = 3;
+pub const TIPC_NLA_BEARER_UDP_OPTS: u16 = 4;
+
+pub const TIPC_NLA_UDP_UNSPEC: u16 = 0;
+pub const TIPC_NLA_UDP_LOCAL: u16 = 1;
+pub const TIPC_NLA_UDP_REMOTE: u16 = 2;
+pub const TIPC_NLA_UDP_MULTI_REMOTEIP: u16 = 3;
+
+pub const TIPC_NLA_SOCK_UNSPEC: u16 = 0;
+pub const TIPC_NLA_SOCK_ADDR: u16 = 1;
+pub const TIPC_NLA_SOCK_REF: u16 = 2;
+pub const TIPC_NLA_SOCK_CON: u16 = 3;
+pub const TIPC_NLA_SOCK_HAS_PUBL: u16 = 4;
+pub const TIPC_NLA_SOCK_STAT: u16 = 5;
+pub const TIPC_NLA_SOCK_TYPE: u16 = 6;
+pub const TIPC_NLA_SOCK_INO: u16 = 7;
+pub const TIPC_NLA_SOCK_UID: u16 = 8;
+pub const TIPC_NLA_SOCK_TIPC_STATE: u16 = 9;
+pub const TIPC_NLA_SOCK_COOKIE: u16 = 10;
+pub const TIPC_NLA_SOCK_PAD: u16 = 11;
+pub const TIPC_NLA_SOCK_GROUP: u16 = 12;
+
+pub const TIPC_NLA_LINK_UNSPEC: u16 = 0;
+pub const TIPC_NLA_LINK_NAME: u16 = 1;
+pub const TIPC_NLA_LINK_DEST: u16 = 2;
+pub const TIPC_NLA_LINK_MTU: u16 = 3;
+pub const TIPC_NLA_LINK_BROADCAST: u16 = 4;
+pub const TIPC_NLA_LINK_UP: u16 = 5;
+pub const TIPC_NLA_LINK_ACTIVE: u16 = 6;
+pub const TIPC_NLA_LINK_PROP: u16 = 7;
+pub const TIPC_NLA_LINK_STATS: u16 = 8;
+pub const TIPC_NLA_LINK_RX: u16 = 9;
+pub const TIPC_NLA_LINK_TX: u16 = 10;
+
+pub const TIPC_NLA_MEDIA_UNSPEC: u16 = 0;
+pub const TIPC_NLA_MEDIA_NAME: u16 = 1;
+pub const TIPC_NLA_MEDIA_PROP: u16 = 2;
+
+pub const TIPC_NLA_NODE_UNSPEC: u16 = 0;
+pub const TIPC_NLA_NODE_ADDR: u16 = 1;
+pub const TIPC_NLA_NODE_UP: u16 = 2;
+pub const TIPC_NLA_NODE_ID: u16 = 3;
+pub const TIPC_NLA_NODE_KEY: u16 = 4;
+pub const TIPC_NLA_NODE_KEY_MASTER: u16 = 5;
+pub const TIPC_NLA_NODE_REKEYING: u16 = 6;
+
+pub const TIPC_NLA_NET_UNSPEC: u16 = 0;
+pub const TIPC_NLA_NET_ID: u16 = 1;
+pub const TIPC_NLA_NET_ADDR: u16 = 2;
+pub const TIPC_NLA_NET_NODEID: u16 = 3;
+pub const TIPC_NLA_NET_NODEID_W1: u16 = 4;
+pub const TIPC_NLA_NET_ADDR_LEGACY: u16 = 5;
+
+pub const TIPC_NLA_NAME_TABLE_UNSPEC: u16 = 0;
+pub const TIPC_NLA_NAME_TABLE_PUBL: u16 = 1;
+
+pub const TIPC_NLA_MON_UNSPEC: u16 = 0;
+pub const TIPC_NLA_MON_ACTIVATION_THRESHOLD: u16 = 1;
+pub const TIPC_NLA_MON_REF: u16 = 2;
+pub const TIPC_NLA_MON_ACTIVE: u16 = 3;
+pub const TIPC_NLA_MON_BEARER_NAME: u16 = 4;
+pub const TIPC_NLA_MON_PEERCNT: u16 = 5;
+pub const TIPC_NLA_MON_LISTGEN: u16 = 6;
+
+pub const TIPC_NLA_PUBL_UNSPEC: u16 = 0;
+pub const TIPC_NLA_PUBL_TYPE: u16 = 1;
+pub const TIPC_NLA_PUBL_LOWER: u16 = 2;
+pub const TIPC_NLA_PUBL_UPPER: u16 = 3;
+pub const TIPC_NLA_PUBL_SCOPE: u16 = 4;
+pub const TIPC_NLA_PUBL_NODE: u16 = 5;
+pub const TIPC_NLA_PUBL_REF: u16 = 6;
+pub const TIPC_NLA_PUBL_KEY: u16 = 7;
+
+pub const TIPC_NLA_MON_PEER_UNSPEC: u16 = 0;
+pub const TIPC_NLA_MON_PEER_ADDR: u16 = 1;
+pub const TIPC_NLA_MON_PEER_DOMGEN: u16 = 2;
+pub const TIPC_NLA_MON_PEER_APPLIED: u16 = 3;
+pub const TIPC_NLA_MON_PEER_UPMAP: u16 = 4;
+pub const TIPC_NLA_MON_PEER_MEMBERS: u16 = 5;
+pub const TIPC_NLA_MON_PEER_UP: u16 = 6;
+pub const TIPC_NLA_MON_PEER_HEAD: u16 = 7;
+pub const TIPC_NLA_MON_PEER_LOCAL: u16 = 8;
+pub const TIPC_NLA_MON_PEER_PAD: u16 = 9;
+
+pub const TIPC_NLA_CON_UNSPEC: u16 = 0;
+pub const TIPC_NLA_CON_FLAG: u16 = 1;
+pub const TIPC_NLA_CON_NODE: u16 = 2;
+pub const TIPC_NLA_CON_SOCK: u16 = 3;
+pub const TIPC_NLA_CON_TYPE: u16 = 4;
+pub const TIPC_NLA_CON_INST: u16 = 5;
+
+pub const TIPC_NLA_SOCK_STAT_RCVQ: u16 = 0;
+pub const TIPC_NLA_SOCK_STAT_SENDQ: u16 = 1;
+pub const TIPC_NLA_SOCK_STAT_LINK_CONG: u16 = 2;
+pub const TIPC_NLA_SOCK_STAT_CONN_CONG: u16 = 3;
+pub const TIPC_NLA_SOCK_STAT_DROP: u16 = 4;
+
+pub const TIPC_NLA_PROP_UNSPEC: u16 = 0;
+pub const TIPC_NLA_PROP_PRIO: u16 = 1;
+pub const TIPC_NLA_PROP_TOL: u16 = 2;
+pub const TIPC_NLA_PROP_WIN: u16 = 3;
+pub const TIPC_NLA_PROP_MTU: u16 = 4;
+pub const TIPC_NLA_PROP_BROADCAST: u16 = 5;
+pub const TIPC_NLA_PROP_BROADCAST_RATIO: u16 = 6;
+
+pub const TIPC_NLA_STATS_UNSPEC: u16 = 0;
+pub const TIPC_NLA_STATS_RX_INFO: u16 = 1;
+pub const TIPC_NLA_STATS_RX_FRAGMENTS: u16 = 2;
+pub const TIPC_NLA_STATS_RX_FRAGMENTED: u16 = 3;
+pub const TIPC_NLA_STATS_RX_BUNDLES: u16 = 4;
+pub const TIPC_NLA_STATS_RX_BUNDLED: u16 = 5;
+pub const TIPC_NLA_STATS_TX_INFO: u16 = 6;
+pub const TIPC_NLA_STATS_TX_FRAGMENTS: u16 = 7;
+pub const TIPC_NLA_STATS_TX_FRAGMENTED: u16 = 8;
+pub const TIPC_NLA_STATS_TX_BUNDLES: u16 = 9;
+pub const TIPC_NLA_STATS_TX_BUNDLED: u16 = 10;
+pub const TIPC_NLA_STATS_MSG_PROF_TOT: u16 = 11;
+pub const TIPC_NLA_STATS_MSG_LEN_CNT: u16 = 12;
+pub const TIPC_NLA_STATS_MSG_LEN_TOT: u16 = 13;
+pub const TIPC_NLA_STATS_MSG_LEN_P0: u16 = 14;
+pub const TIPC_NLA_STATS_MSG_LEN_P1: u16 = 15;
+pub const TIPC_NLA_STATS_MSG_LEN_P2: u16 = 16;
+pub const TIPC_NLA_STATS_MSG_LEN_P3: u16 = 17;
+pub const TIPC_NLA_STATS_MSG_LEN_P4: u16 = 18;
+pub const TIPC_NLA_STATS_MSG_LEN_P5: u16 = 19;
+pub const TIPC_NLA_STATS_MSG_LEN_P6: u16 = 20;
+pub const TIPC_NLA_STATS_RX_STATES: u16 = 21;
+pub const TIPC_NLA_STATS_RX_PROBES: u16 = 22;
+pub const TIPC_NLA_STATS_RX_NACKS: u16 = 23;
+pub const TIPC_NLA_STATS_RX_DEFERRED: u16 = 24;
+pub const TIPC_NLA_STATS_TX_STATES: u16 = 25;
+pub const TIPC_NLA_STATS_TX_PROBES: u16 = 26;
+pub const TIPC_NLA_STATS_TX_NACKS: u16 = 27;
+pub const TIPC_NLA_STATS_TX_ACKS: u16 = 28;
+pub const TIPC_NLA_STATS_RETRANSMITTED: u16 = 29;
+pub const TIPC_NLA_STATS_DUPLICATES: u16 = 30;
+pub const TIPC_NLA_STATS_LINK_CONGS: u16 = 31;
+pub const TIPC_NLA_STATS_MAX_QUEUE: u16 = 32;
+pub const TIPC_NLA_STATS_AVG_QUEUE: u16 = 33;
+
+pub const TIPC_MIN_LINK_PRI: u32 = 0;
+pub const TIPC_DEF_LINK_PRI: u32 = 10;
+pub const TIPC_MAX_LINK_PRI: u32 = 31;
+pub const TIPC_MEDIA_LINK_PRI: u32 = TIPC_MAX_LINK_PRI + 1;
+
+pub const TIPC_MIN_LINK_TOL: u32 = 50;
+pub const TIPC_DEF_LINK_TOL: u32 = 1500;
+pub const TIPC_MAX_LINK_TOL: u32 = 30000;
+
+pub const TIPC_MIN_LINK_WIN: u32 = 16;
+pub const TIPC_DEF_LINK_WIN: u32 = 50;
+pub const TIPC_MAX_LINK_WIN: u32 = 8191;
+
+pub const TIPC_DEF_LINK_UDP_MTU: u32 = 14000;
+
+pub const BCLINK_MODE_BCAST: u32 = 0x1;
+pub const BCLINK_MODE_RCAST: u32 = 0x2;
+pub const BCLINK_MODE_SEL: u32 = 0x4;
+
+/* the minimal bearer MTU plus the IP and UDP headers, from net/tipc/udp_media.h */
+pub const TIPC_MIN_UDP_MTU: u32 = 100 + 20 + 8;
+
+/* from linux/sock_diag.h and linux/tipc_sockets_diag.h */
+
+pub const SOCK_DIAG_BY_FAMILY: u16 = 20;
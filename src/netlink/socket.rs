@@ -0,0 +1,177 @@
+//! Socket table dump.
+
+use std::collections::HashMap;
+use std::io;
+
+use crate::addr::{ServiceAddr, ServiceRange, SocketAddr};
+
+use super::{
+    attr::{Attrs, FromAttrs, Message},
+    open, raw, transact, Client, Publication,
+};
+
+/// A local TIPC socket.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SocketInfo {
+    /// The socket address, the port reference and the own node.
+    pub addr: SocketAddr,
+    /// The service ranges which the socket is bound to.
+    pub bindings: Vec<ServiceRange>,
+    /// The peer socket address if the socket is connected.
+    pub peer: Option<SocketAddr>,
+    /// The service which the socket connected to by the service address.
+    pub peer_service: Option<ServiceAddr>,
+    /// The queue depths, if the `sock_diag` interface is available.
+    pub queues: Option<Queues>,
+    has_publ: bool,
+}
+
+/// The queue depths of a local TIPC socket.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Queues {
+    /// The messages waiting in the receive queue.
+    pub recv: u32,
+    /// The messages waiting in the send queue.
+    pub send: u32,
+    /// The messages dropped by the socket.
+    pub drops: u32,
+}
+
+impl SocketInfo {
+    /// The port reference of the socket.
+    pub fn port(&self) -> u32 {
+        self.addr.port()
+    }
+}
+
+impl FromAttrs for SocketInfo {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let mut sock = SocketInfo::default();
+        let (mut port, mut node) = (0, 0);
+
+        for attr in attrs {
+            match attr.ty {
+                raw::TIPC_NLA_SOCK_REF => port = attr.u32()?,
+                raw::TIPC_NLA_SOCK_ADDR => node = attr.u32()?,
+                raw::TIPC_NLA_SOCK_HAS_PUBL => sock.has_publ = true,
+                raw::TIPC_NLA_SOCK_CON => {
+                    let con = attr.nested();
+                    let (mut port, mut node) = (0, 0);
+                    let (mut ty, mut instance) = (None, 0);
+
+                    for attr in con {
+                        match attr.ty {
+                            raw::TIPC_NLA_CON_NODE => node = attr.u32()?,
+                            raw::TIPC_NLA_CON_SOCK => port = attr.u32()?,
+                            raw::TIPC_NLA_CON_TYPE => ty = Some(attr.u32()?),
+                            raw::TIPC_NLA_CON_INST => instance = attr.u32()?,
+                            _ => {}
+                        }
+                    }
+
+                    sock.peer = Some(SocketAddr::new(port, node));
+                    sock.peer_service = ty.map(|ty| ServiceAddr::new(ty, instance));
+                }
+                raw::TIPC_NLA_SOCK_STAT => {
+                    let mut queues = Queues::default();
+
+                    for attr in attr.nested() {
+                        match attr.ty {
+                            raw::TIPC_NLA_SOCK_STAT_RCVQ => queues.recv = attr.u32()?,
+                            raw::TIPC_NLA_SOCK_STAT_SENDQ => queues.send = attr.u32()?,
+                            raw::TIPC_NLA_SOCK_STAT_DROP => queues.drops = attr.u32()?,
+                            _ => {}
+                        }
+                    }
+
+                    sock.queues = Some(queues);
+                }
+                _ => {}
+            }
+        }
+
+        sock.addr = SocketAddr::new(port, node);
+
+        Ok(sock)
+    }
+}
+
+/// Dumps the queue depths of the local sockets over the `sock_diag` interface.
+///
+/// Returns `None` if the interface is not available, e.g. the `tipc_diag` module is not loaded.
+fn queues() -> io::Result<Option<HashMap<u32, Queues>>> {
+    let diag = match open(libc::NETLINK_SOCK_DIAG) {
+        Err(ref err) if unavailable(err) => return Ok(None),
+        res => res?,
+    };
+
+    // struct tipc_sock_diag_req { sdiag_family, sdiag_protocol, pad, tidiag_states }
+    let mut req = vec![libc::AF_TIPC as u8, 0, 0, 0];
+    req.extend_from_slice(&u32::MAX.to_ne_bytes());
+
+    let msg = Message::with_header(raw::SOCK_DIAG_BY_FAMILY, libc::NLM_F_DUMP as u16, &req);
+    let mut queues = HashMap::new();
+
+    let res = transact(&diag, 1, msg, |reply| {
+        if let Some(attrs) = Attrs::new(reply.payload).nested(raw::TIPC_NLA_SOCK) {
+            let sock = SocketInfo::from_attrs(attrs)?;
+
+            if let Some(q) = sock.queues {
+                queues.insert(sock.port(), q);
+            }
+        }
+
+        Ok(())
+    });
+
+    match res {
+        Err(ref err) if unavailable(err) => Ok(None),
+        res => res.map(|_| Some(queues)),
+    }
+}
+
+fn unavailable(err: &io::Error) -> bool {
+    matches!(
+        err.raw_os_error(),
+        Some(libc::ENOENT) | Some(libc::EPROTONOSUPPORT) | Some(libc::EOPNOTSUPP)
+    )
+}
+
+impl Client {
+    /// Lists the local sockets with their bindings, connections and queues.
+    ///
+    /// The queue depths come from the `sock_diag` interface, which requires the `tipc_diag` module,
+    /// they are left empty if it is not available.
+    pub fn sockets(&self) -> io::Result<Vec<SocketInfo>> {
+        let mut socks: Vec<SocketInfo> =
+            self.dump(raw::TIPC_NL_SOCK_GET, raw::TIPC_NLA_SOCK, |_| {})?;
+
+        if let Some(mut queues) = queues()? {
+            for sock in socks.iter_mut() {
+                sock.queues = queues.remove(&sock.port());
+            }
+        }
+
+        for sock in socks.iter_mut().filter(|sock| sock.has_publ) {
+            sock.bindings = match self.bindings(sock.port()) {
+                // the socket was closed after the dump
+                Err(ref err) if err.raw_os_error() == Some(libc::EINVAL) => vec![],
+                res => res?,
+            };
+        }
+
+        Ok(socks)
+    }
+
+    /// Lists the service ranges which the local socket is bound to.
+    pub fn bindings(&self, port: u32) -> io::Result<Vec<ServiceRange>> {
+        let publs: Vec<Publication> =
+            self.dump(raw::TIPC_NL_PUBL_GET, raw::TIPC_NLA_PUBL, |msg| {
+                msg.nest(raw::TIPC_NLA_SOCK, |msg| {
+                    msg.put_u32(raw::TIPC_NLA_SOCK_REF, port);
+                });
+            })?;
+
+        Ok(publs.into_iter().map(|publ| publ.service).collect())
+    }
+}
@@ -0,0 +1,227 @@
+//! UDP bearer options.
+
+use core::convert::TryInto;
+
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+use super::{
+    attr::{invalid_data, Attrs, FromAttrs, Message},
+    bearer::check_name,
+    raw, Client,
+};
+
+/// The default UDP port of the bearers.
+pub const DEFAULT_UDP_PORT: u16 = 6118;
+
+/// The addresses of an UDP bearer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Udp {
+    /// The local address which the bearer is bound to.
+    pub local: SocketAddr,
+    /// The multicast address used for the neighbor discovery, or the first unicast peer.
+    pub remote: SocketAddr,
+    /// More unicast peers were added to the bearer.
+    pub multi_remote: bool,
+}
+
+impl Udp {
+    /// Constructs the options with the local and remote address.
+    pub fn new<L: Into<SocketAddr>, R: Into<SocketAddr>>(local: L, remote: R) -> Self {
+        Udp {
+            local: local.into(),
+            remote: remote.into(),
+            multi_remote: false,
+        }
+    }
+
+    /// The peers are discovered with the multicast address.
+    pub fn is_multicast(&self) -> bool {
+        self.remote.ip().is_multicast()
+    }
+
+    pub(crate) fn put(&self, msg: &mut Message) {
+        msg.nest(raw::TIPC_NLA_BEARER_UDP_OPTS, |msg| {
+            msg.put_bytes(raw::TIPC_NLA_UDP_LOCAL, &to_sockaddr(&self.local))
+                .put_bytes(raw::TIPC_NLA_UDP_REMOTE, &to_sockaddr(&self.remote));
+        });
+    }
+}
+
+impl FromAttrs for Udp {
+    fn from_attrs(attrs: Attrs) -> io::Result<Self> {
+        let local = attrs
+            .get(raw::TIPC_NLA_UDP_LOCAL)
+            .ok_or_else(|| invalid_data("missing local address"))
+            .and_then(|attr| from_sockaddr(attr.data))?;
+        let remote = attrs
+            .get(raw::TIPC_NLA_UDP_REMOTE)
+            .ok_or_else(|| invalid_data("missing remote address"))
+            .and_then(|attr| from_sockaddr(attr.data))?;
+
+        Ok(Udp {
+            local,
+            remote,
+            multi_remote: attrs.get(raw::TIPC_NLA_UDP_MULTI_REMOTEIP).is_some(),
+        })
+    }
+}
+
+/// Encodes the address as `struct sockaddr_in` or `struct sockaddr_in6`.
+fn to_sockaddr(addr: &SocketAddr) -> Vec<u8> {
+    let mut buf = vec![];
+
+    match addr {
+        SocketAddr::V4(addr) => {
+            buf.extend_from_slice(&(libc::AF_INET as u16).to_ne_bytes());
+            buf.extend_from_slice(&addr.port().to_be_bytes());
+            buf.extend_from_slice(&addr.ip().octets());
+            buf.resize(16, 0);
+        }
+        SocketAddr::V6(addr) => {
+            buf.extend_from_slice(&(libc::AF_INET6 as u16).to_ne_bytes());
+            buf.extend_from_slice(&addr.port().to_be_bytes());
+            buf.extend_from_slice(&addr.flowinfo().to_be_bytes());
+            buf.extend_from_slice(&addr.ip().octets());
+            buf.extend_from_slice(&addr.scope_id().to_ne_bytes());
+        }
+    }
+
+    buf
+}
+
+/// Decodes the address from `struct sockaddr_in` or `struct sockaddr_in6`.
+fn from_sockaddr(buf: &[u8]) -> io::Result<SocketAddr> {
+    let invalid = || invalid_data("invalid socket address");
+    let family = buf
+        .get(..2)
+        .and_then(|b| b.try_into().ok())
+        .map(u16::from_ne_bytes)
+        .ok_or_else(invalid)?;
+
+    match i32::from(family) {
+        libc::AF_INET if buf.len() >= 8 => {
+            let port = u16::from_be_bytes(buf[2..4].try_into().unwrap());
+            let ip: [u8; 4] = buf[4..8].try_into().unwrap();
+
+            Ok(SocketAddrV4::new(Ipv4Addr::from(ip), port).into())
+        }
+        libc::AF_INET6 if buf.len() >= 28 => {
+            let port = u16::from_be_bytes(buf[2..4].try_into().unwrap());
+            let flowinfo = u32::from_be_bytes(buf[4..8].try_into().unwrap());
+            let ip: [u8; 16] = buf[8..24].try_into().unwrap();
+            let scope_id = u32::from_ne_bytes(buf[24..28].try_into().unwrap());
+
+            Ok(SocketAddrV6::new(Ipv6Addr::from(ip), port, flowinfo, scope_id).into())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn put_remote(msg: &mut Message, name: &str, remote: &SocketAddr) {
+    msg.nest(raw::TIPC_NLA_BEARER, |msg| {
+        msg.put_str(raw::TIPC_NLA_BEARER_NAME, name)
+            .nest(raw::TIPC_NLA_BEARER_UDP_OPTS, |msg| {
+                msg.put_bytes(raw::TIPC_NLA_UDP_REMOTE, &to_sockaddr(remote));
+            });
+    });
+}
+
+impl Client {
+    /// Adds an unicast peer to the UDP bearer, which doesn't support the multicast.
+    ///
+    /// The peer bearer is usually bound to the `DEFAULT_UDP_PORT`.
+    pub fn add_udp_remote<A: Into<SocketAddr>>(&self, name: &str, remote: A) -> io::Result<()> {
+        check_name(name)?;
+
+        let remote = remote.into();
+
+        if remote.ip().is_multicast() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("multicast remote address: {}", remote.ip()),
+            ));
+        }
+
+        self.execute(raw::TIPC_NL_BEARER_ADD, |msg| {
+            put_remote(msg, name, &remote)
+        })
+    }
+
+    /// Lists the remote addresses of the UDP bearer.
+    pub fn udp_remotes(&self, name: &str) -> io::Result<Vec<SocketAddr>> {
+        check_name(name)?;
+
+        let mut msg = self.message(raw::TIPC_NL_UDP_GET_REMOTEIP, libc::NLM_F_DUMP);
+
+        msg.nest(raw::TIPC_NLA_BEARER, |msg| {
+            msg.put_str(raw::TIPC_NLA_BEARER_NAME, name);
+        });
+
+        let mut remotes = vec![];
+
+        self.transact(msg, |reply| {
+            if let Some(attr) = reply.attrs().get(raw::TIPC_NLA_UDP_REMOTE) {
+                remotes.push(from_sockaddr(attr.data)?);
+            }
+
+            Ok(())
+        })?;
+
+        Ok(remotes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::netlink::attr::replies;
+
+    #[test]
+    fn sockaddr() {
+        for addr in &[
+            "192.168.1.1:6118".parse::<SocketAddr>().unwrap(),
+            "[ff02::1]:6118".parse().unwrap(),
+        ] {
+            assert_eq!(&from_sockaddr(&to_sockaddr(addr)).unwrap(), addr);
+        }
+
+        assert!(from_sockaddr(&[0; 16]).is_err());
+        assert!(from_sockaddr(&to_sockaddr(&"[::1]:1".parse().unwrap())[..16]).is_err());
+    }
+
+    #[test]
+    fn remote() {
+        let mut msg = Message::new(0x1234, 0, raw::TIPC_NL_BEARER_ADD, 1);
+
+        put_remote(
+            &mut msg,
+            "udp:b1",
+            &SocketAddr::new([192, 168, 1, 2].into(), DEFAULT_UDP_PORT),
+        );
+
+        let buf = msg.finish(1);
+        let reply = replies(&buf).next().unwrap().unwrap();
+        let bearer = reply.attrs().nested(raw::TIPC_NLA_BEARER).unwrap();
+        let remote = bearer
+            .nested(raw::TIPC_NLA_BEARER_UDP_OPTS)
+            .unwrap()
+            .get(raw::TIPC_NLA_UDP_REMOTE)
+            .unwrap();
+
+        assert_eq!(
+            bearer
+                .get(raw::TIPC_NLA_BEARER_NAME)
+                .unwrap()
+                .str()
+                .unwrap(),
+            "udp:b1"
+        );
+        assert_eq!(remote.data.len(), 16);
+        assert_eq!(remote.data[..2], (libc::AF_INET as u16).to_ne_bytes());
+        assert_eq!(remote.data[2..4], [0x17, 0xe6]);
+        assert_eq!(remote.data[4..8], [192, 168, 1, 2]);
+        assert_eq!(remote.data[8..], [0; 8]);
+    }
+}
@@ -0,0 +1,400 @@
+//! A multi-node test harness with the network namespaces.
+//!
+//! Each node of a `Cluster` is a network namespace, the nodes are fully meshed with the veth pairs,
+//! and an Ethernet bearer is enabled on each of the interfaces.
+//! The interface of a node towards the node `j` is named `veth<j>`.
+//!
+//! The harness requires the root, the `tipc` kernel module and the `ip` command of `iproute2`,
+//! the faults are injected with the `tc` command and the `netem` queueing discipline.
+//! The closures passed to `Node::run` are executed in a thread which has entered the namespace,
+//! so the sockets and the netlink clients of this crate opened there belong to the node.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::panic;
+use std::process::Command;
+use std::thread;
+
+use crate::{
+    addr::{Instance, Scope},
+    fault::{Fault, Inject},
+    netlink::{self, Bearer, NodeId},
+    sock::{self, IntoResult},
+    topo::{self, Node as NodeEvent},
+};
+
+/// Runs the `ip` command.
+fn ip(args: &[&str]) -> io::Result<()> {
+    run("ip", args)
+}
+
+/// Runs the `tc` command.
+fn tc(args: &[&str]) -> io::Result<()> {
+    run("tc", args)
+}
+
+fn run(program: &str, args: &[&str]) -> io::Result<()> {
+    let output = Command::new(program).args(args).output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "{} {}: {}",
+                program,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ))
+    }
+}
+
+/// A node of the cluster.
+#[derive(Debug)]
+pub struct Node {
+    index: usize,
+    netns: String,
+    node_id: NodeId,
+}
+
+impl Node {
+    /// The index of the node in the cluster.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The name of the network namespace.
+    pub fn netns(&self) -> &str {
+        &self.netns
+    }
+
+    /// The identity of the node.
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    /// The name of the interface towards the peer node.
+    pub fn interface(&self, peer: &Node) -> String {
+        format!("veth{}", peer.index)
+    }
+
+    /// The node address hashed from the identity.
+    pub fn addr(&self) -> io::Result<Instance> {
+        self.run(|| sock::rdm()?.local_addr().map(|addr| addr.node()))?
+    }
+
+    /// Runs the closure in the network namespace of the node.
+    ///
+    /// A panic in the closure is propagated to the caller.
+    pub fn run<F, R>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        thread::scope(|s| {
+            s.spawn(|| {
+                self.enter()?;
+
+                Ok(f())
+            })
+            .join()
+            .unwrap_or_else(|err| panic::resume_unwind(err))
+        })
+    }
+
+    /// Moves the current thread into the network namespace.
+    fn enter(&self) -> io::Result<()> {
+        let netns = File::open(format!("/run/netns/{}", self.netns))?;
+
+        unsafe { libc::setns(netns.as_raw_fd(), libc::CLONE_NEWNET) }.into_result()
+    }
+}
+
+/// The nodes in the network namespaces.
+#[derive(Debug)]
+pub struct Cluster {
+    nodes: Vec<Node>,
+}
+
+impl Drop for Cluster {
+    fn drop(&mut self) {
+        for node in &self.nodes {
+            let _ = ip(&["netns", "delete", &node.netns]);
+        }
+    }
+}
+
+impl Cluster {
+    /// Creates a cluster of the nodes, named from `node1` to `node<n>`.
+    pub fn new(n: usize) -> io::Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        if unsafe { libc::geteuid() } != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "network namespaces require root",
+            ));
+        }
+
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        let mut cluster = Cluster { nodes: vec![] };
+
+        for index in 1..=n {
+            let node = Node {
+                index,
+                netns: format!("tipc-{}-{}-{}", std::process::id(), id, index),
+                node_id: format!("node{}", index).parse()?,
+            };
+
+            ip(&["netns", "add", &node.netns])?;
+            cluster.nodes.push(node);
+        }
+
+        for (i, node) in cluster.nodes.iter().enumerate() {
+            ip(&["-n", &node.netns, "link", "set", "lo", "up"])?;
+
+            for peer in &cluster.nodes[i + 1..] {
+                let ifname = node.interface(peer);
+                let peer_ifname = peer.interface(node);
+
+                ip(&[
+                    "link",
+                    "add",
+                    &ifname,
+                    "netns",
+                    &node.netns,
+                    "type",
+                    "veth",
+                    "peer",
+                    "name",
+                    &peer_ifname,
+                    "netns",
+                    &peer.netns,
+                ])?;
+                ip(&["-n", &node.netns, "link", "set", &ifname, "up"])?;
+                ip(&["-n", &peer.netns, "link", "set", &peer_ifname, "up"])?;
+            }
+        }
+
+        for node in &cluster.nodes {
+            let bearers = cluster
+                .nodes
+                .iter()
+                .filter(|peer| peer.index != node.index)
+                .map(|peer| Bearer::eth(&node.interface(peer)))
+                .collect::<Vec<_>>();
+
+            node.run(|| {
+                let client = netlink::connect()?;
+
+                client.set_node_id(node.node_id)?;
+
+                bearers
+                    .iter()
+                    .try_for_each(|bearer| client.enable_bearer(bearer))
+            })??;
+        }
+
+        Ok(cluster)
+    }
+
+    /// The nodes of the cluster.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the node with the index, starting from 1.
+    pub fn node(&self, index: usize) -> &Node {
+        &self.nodes[index - 1]
+    }
+
+    /// Runs the closure in each node concurrently, and returns their results in the order of the nodes.
+    pub fn run<F, R>(&self, f: F) -> io::Result<Vec<R>>
+    where
+        F: Fn(&Node) -> R + Sync,
+        R: Send,
+    {
+        thread::scope(|s| {
+            let handles = self
+                .nodes
+                .iter()
+                .map(|node| {
+                    let f = &f;
+
+                    s.spawn(move || {
+                        node.enter()?;
+
+                        Ok(f(node))
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|err| panic::resume_unwind(err))
+                })
+                .collect()
+        })
+    }
+
+    /// Waits until every node sees all nodes of the cluster up.
+    pub fn wait_up(&self, timeout: Duration) -> io::Result<()> {
+        let n = self.nodes.len();
+
+        self.run(|_| {
+            let srv = topo::connect(Scope::Global)?;
+            let mut up = BTreeSet::new();
+
+            srv.subscribe(topo::NEIGHBOR_NODES.timeout(timeout))?;
+
+            while up.len() < n {
+                match NodeEvent::from(srv.recv()?) {
+                    NodeEvent::Up(node) => up.insert(node),
+                    NodeEvent::Down(node) => up.remove(&node),
+                };
+            }
+
+            Ok(())
+        })?
+        .into_iter()
+        .collect()
+    }
+}
+
+/// The faults are injected into the nodes identified by their indexes.
+///
+/// The impairments are applied by `netem` to the egress of the interface towards the peer,
+/// and a killed node is isolated by setting all its interfaces down.
+///
+/// Unlike a node killed in the `mock::Fabric`, the isolated node keeps running:
+/// its sockets stay open and its own bindings are kept, only its links go down,
+/// so the peers see it down, and it rejoins the cluster if its interfaces are set up again.
+impl Inject for Cluster {
+    type Node = usize;
+
+    fn inject(&self, fault: Fault<usize>) -> io::Result<()> {
+        let find = |index: usize| {
+            self.nodes
+                .get(index.wrapping_sub(1))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "node not found"))
+        };
+
+        match fault {
+            Fault::Impair {
+                node: index,
+                peer,
+                impairment,
+            } => {
+                impairment.validate()?;
+
+                let node = find(index)?;
+                let ifname = node.interface(find(peer)?);
+
+                if impairment.is_empty() {
+                    let _ = tc(&["-n", &node.netns, "qdisc", "del", "dev", &ifname, "root"]);
+
+                    Ok(())
+                } else {
+                    tc(&[
+                        "-n",
+                        &node.netns,
+                        "qdisc",
+                        "replace",
+                        "dev",
+                        &ifname,
+                        "root",
+                        "netem",
+                        "delay",
+                        &format!("{}us", impairment.delay.as_micros()),
+                        "loss",
+                        &format!("{}%", impairment.loss),
+                        "reorder",
+                        &format!("{}%", impairment.reorder),
+                    ])
+                }
+            }
+            Fault::BearerDown { node: index, peer } => {
+                let node = find(index)?;
+                let name = format!("eth:{}", node.interface(find(peer)?));
+
+                node.run(|| netlink::connect()?.disable_bearer(&name))?
+            }
+            Fault::BearerUp { node: index, peer } => {
+                let node = find(index)?;
+                let bearer = Bearer::eth(&node.interface(find(peer)?));
+
+                node.run(|| netlink::connect()?.enable_bearer(&bearer))?
+            }
+            Fault::Kill(index) => {
+                let node = find(index)?;
+
+                self.nodes
+                    .iter()
+                    .filter(|peer| peer.index != node.index)
+                    .try_for_each(|peer| {
+                        ip(&[
+                            "-n",
+                            &node.netns,
+                            "link",
+                            "set",
+                            &node.interface(peer),
+                            "down",
+                        ])
+                    })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Requires the root and the `tipc` module.
+    #[test]
+    #[ignore]
+    fn cluster() {
+        let cluster = Cluster::new(3).unwrap();
+
+        cluster.wait_up(Duration::from_secs(10)).unwrap();
+
+        let addrs = cluster
+            .run(|node| node.addr())
+            .unwrap()
+            .into_iter()
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(addrs.iter().collect::<BTreeSet<_>>().len(), 3);
+
+        let node = cluster.node(1);
+        let ifname = node.interface(cluster.node(3));
+        let evt = node
+            .run(|| -> io::Result<NodeEvent> {
+                let nodes = topo::neighbor_nodes(Scope::Global)?;
+
+                for _ in 0..3 {
+                    assert!(nodes.recv()?.available());
+                }
+
+                ip(&["-n", node.netns(), "link", "set", &ifname, "down"])?;
+
+                nodes.recv()
+            })
+            .unwrap()
+            .unwrap();
+
+        assert!(!evt.available());
+        assert_eq!(evt.instance(), addrs[2]);
+    }
+}
@@ -1,4 +1,5 @@
 use core::convert::TryInto;
+use core::fmt;
 use core::iter;
 use core::marker::PhantomData;
 use core::mem::{self, MaybeUninit};
@@ -9,12 +10,13 @@ use core::ptr::NonNull;
 use core::slice;
 use core::time::Duration;
 
+use std::error;
 use std::ffi::CStr;
 use std::io;
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 
 use bitflags::bitflags;
-use failure::{err_msg, format_err, Error, Fail};
+use failure::{err_msg, format_err};
 
 use crate::{
     addr::{Instance, Scope, ServiceAddr, ServiceRange, SocketAddr, Visibility, TIPC_ADDR_MCAST},
@@ -451,24 +453,43 @@ impl<T> Deref for Connected<T> {
     }
 }
 
+impl<T: AsRawFd> AsRawFd for Connected<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
 impl<T> Connected<T>
 where
     T: AsRef<Socket>,
 {
     /// Receives data from the socket.
     ///
-    /// On success, returns the number of bytes read.
+    /// On success, returns the number of bytes read, or zero if the peer has shut down the connection.
+    /// If the connection was aborted, an error of the kind mapped from the `ErrorCode` is returned with it,
+    /// and if the message didn't fit the buffer, an `InvalidData` error is returned with `Truncated`.
     pub fn recv<B: AsMut<[u8]>>(&self, buf: B) -> io::Result<usize> {
-        self.0.as_ref().recv(buf, Recv::empty())
+        self.recv_with_flags(buf, Recv::empty())
     }
 
     /// Receives data on the socket from the remote address to which it is connected,
     /// without removing that data from the queue.
     ///
     /// On success, returns the number of bytes peeked. Successive calls return the same data.
-    /// This is accomplished by passing `MSG_PEEK` as a flag to the underlying `recv` system call.
+    /// This is accomplished by passing `MSG_PEEK` as a flag to the underlying `recvmsg` system call.
     pub fn peek<B: AsMut<[u8]>>(&self, buf: B) -> io::Result<usize> {
-        self.0.as_ref().recv(buf, Recv::PEEK)
+        self.recv_with_flags(buf, Recv::PEEK)
+    }
+
+    fn recv_with_flags<B: AsMut<[u8]>>(&self, buf: B, flags: Recv) -> io::Result<usize> {
+        match self.0.as_ref().recv_msg(buf, flags)? {
+            (RecvMsg::Message { len, truncated, .. }, _) => Truncated::check(len, truncated, flags),
+            (RecvMsg::Rejected(rejected), _) => rejected.err.into_conn_error().map_or(Ok(0), Err),
+            (msg, _) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format_err!("unexpected group event: {:?}", msg),
+            )),
+        }
     }
 
     /// Sends data on the socket to the remote address to which it is connected.
@@ -540,6 +561,15 @@ impl Stream {
     }
 }
 
+impl Connected<SeqPacket> {
+    /// Returns the length of the next message, without removing it from the queue.
+    ///
+    /// The length of the returned data is reported if the connection was aborted.
+    pub fn next_message_len(&self) -> io::Result<Option<usize>> {
+        self.0.next_message_len()
+    }
+}
+
 impl io::Read for Connected<Stream> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.0.as_ref().recv(buf, Recv::empty())
@@ -629,6 +659,11 @@ impl SeqPacket {
         self.0.recv_from(buf, Recv::PEEK)
     }
 
+    /// Returns the length of the next message, without removing it from the queue.
+    pub fn next_message_len(&self) -> io::Result<Option<usize>> {
+        self.0.next_message_len()
+    }
+
     /// Sends data on the socket to the given address. On success, returns the number of bytes written.
     pub fn send_to<T, A>(&self, buf: T, dst: A) -> io::Result<usize>
     where
@@ -699,10 +734,18 @@ impl Datagram {
         self.0.recv_from(buf, Recv::PEEK)
     }
 
+    /// Returns the length of the next message, without removing it from the queue.
+    ///
+    /// `None` is returned for a group membership event.
+    pub fn next_message_len(&self) -> io::Result<Option<usize>> {
+        self.0.next_message_len()
+    }
+
     /// Like `recv_from`, except that it receives into a slice of buffers.
     ///
     /// Data is copied to fill each buffer in order, with the final buffer written to possibly being only partially filled.
     /// This method must behave as a single call to `recv_from` with the buffers concatenated would.
+    /// If the message didn't fit the buffers, an `InvalidData` error is returned with `Truncated`.
     pub fn recv_from_vectored(
         &self,
         bufs: &mut [io::IoSliceMut],
@@ -741,6 +784,29 @@ impl Datagram {
         self.0.send_to_vectored(bufs, addr, Send::empty())
     }
 
+    /// Receives multiple messages with a single system call, blocking until at least one is received.
+    ///
+    /// Each message is received into one of the buffers, with one of the address and control buffers,
+    /// which reports the message or its error. On success, returns the number of the messages received.
+    pub fn recv_batch<B: AsMut<[u8]>>(
+        &self,
+        bufs: &mut [B],
+        msg_bufs: &mut [RecvMsgBuf],
+    ) -> io::Result<usize> {
+        self.0.recv_batch(bufs, msg_bufs, Recv::empty())
+    }
+
+    /// Sends multiple messages, each to its own destination, with a single system call.
+    ///
+    /// At most `MAX_BATCH` messages are sent, on success, returns the number of messages sent.
+    pub fn send_batch<B, A>(&self, msgs: &[(B, A)]) -> io::Result<usize>
+    where
+        B: AsRef<[u8]>,
+        A: ToSocketAddrs,
+    {
+        self.0.send_batch(msgs, Send::empty())
+    }
+
     /// Join a communication group.
     pub fn join<A: ToServiceAddrs>(self, addr: A, flags: Join) -> io::Result<Group<Self>> {
         self.0.join(addr, flags)?;
@@ -762,6 +828,18 @@ impl<T> Deref for Group<T> {
     }
 }
 
+impl<T: AsRawFd> AsRawFd for Group<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl<T: AsRef<Socket>> AsRef<Socket> for Group<T> {
+    fn as_ref(&self) -> &Socket {
+        self.0.as_ref()
+    }
+}
+
 impl<T> Group<T>
 where
     T: AsRef<Socket>,
@@ -797,10 +875,172 @@ where
     }
 }
 
-/// A message was rejected.
-#[derive(Debug, Fail)]
-#[fail(display = "message rejected, {}", _0)]
-pub struct Rejected(u32);
+/// The reason why a message was rejected, or a connection was aborted.
+///
+/// It is reported as the inner error of the `io::Error` converted from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// No socket is bound to the service address.
+    NoName,
+    /// The destination socket doesn't exist.
+    NoPort,
+    /// The destination node is unreachable.
+    NoNode,
+    /// The receive queue of the destination socket is full.
+    Overload,
+    /// The connection was shut down by the peer.
+    ConnShutdown,
+    /// An unknown error code.
+    Unknown(u32),
+}
+
+impl From<u32> for ErrorCode {
+    fn from(code: u32) -> Self {
+        match code {
+            ffi::TIPC_ERR_NO_NAME => ErrorCode::NoName,
+            ffi::TIPC_ERR_NO_PORT => ErrorCode::NoPort,
+            ffi::TIPC_ERR_NO_NODE => ErrorCode::NoNode,
+            ffi::TIPC_ERR_OVERLOAD => ErrorCode::Overload,
+            ffi::TIPC_CONN_SHUTDOWN => ErrorCode::ConnShutdown,
+            _ => ErrorCode::Unknown(code),
+        }
+    }
+}
+
+impl From<ErrorCode> for u32 {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::NoName => ffi::TIPC_ERR_NO_NAME,
+            ErrorCode::NoPort => ffi::TIPC_ERR_NO_PORT,
+            ErrorCode::NoNode => ffi::TIPC_ERR_NO_NODE,
+            ErrorCode::Overload => ffi::TIPC_ERR_OVERLOAD,
+            ErrorCode::ConnShutdown => ffi::TIPC_CONN_SHUTDOWN,
+            ErrorCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorCode::NoName => f.write_str("no service bound to the name"),
+            ErrorCode::NoPort => f.write_str("destination socket not found"),
+            ErrorCode::NoNode => f.write_str("destination node unreachable"),
+            ErrorCode::Overload => f.write_str("destination socket overloaded"),
+            ErrorCode::ConnShutdown => f.write_str("connection shut down"),
+            ErrorCode::Unknown(code) => write!(f, "unknown error {}", code),
+        }
+    }
+}
+
+impl error::Error for ErrorCode {}
+
+impl From<ErrorCode> for io::Error {
+    fn from(code: ErrorCode) -> Self {
+        let kind = match code {
+            ErrorCode::NoName => io::ErrorKind::NotFound,
+            ErrorCode::NoPort => io::ErrorKind::ConnectionRefused,
+            ErrorCode::NoNode => io::ErrorKind::ConnectionReset,
+            ErrorCode::ConnShutdown => io::ErrorKind::ConnectionAborted,
+            ErrorCode::Overload | ErrorCode::Unknown(_) => io::ErrorKind::Other,
+        };
+
+        io::Error::new(kind, code)
+    }
+}
+
+/// The message was truncated to fit the buffer.
+///
+/// It is reported as the inner error of an `io::ErrorKind::InvalidData` error,
+/// the buffer holds the first `len` bytes of the message and the rest was discarded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Truncated {
+    /// The length of the data received into the buffer.
+    pub len: usize,
+}
+
+impl fmt::Display for Truncated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "message truncated to {} bytes", self.len)
+    }
+}
+
+impl error::Error for Truncated {}
+
+impl From<Truncated> for io::Error {
+    fn from(truncated: Truncated) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, truncated)
+    }
+}
+
+impl Truncated {
+    /// Returns the length of the received data, or the error if the message was truncated.
+    ///
+    /// A peeked message is still in the queue, so its truncation is not an error.
+    pub(crate) fn check(len: usize, truncated: bool, flags: Recv) -> io::Result<usize> {
+        if truncated && !flags.contains(Recv::PEEK) {
+            Err(Truncated { len }.into())
+        } else {
+            Ok(len)
+        }
+    }
+}
+
+/// The maximum length of the data returned with a rejected message.
+pub(crate) const MAX_REJECTED_DATA: usize = 1024;
+
+/// The destination of a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Destination {
+    /// The message was sent to a socket, or through a connection.
+    Socket(SocketAddr),
+    /// The message was sent to a service address, or multicast to a service range.
+    Service(ServiceRange),
+}
+
+/// A message returned undelivered.
+///
+/// At most the first 1024 bytes of the message are returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rejected {
+    /// The reason why the message was rejected.
+    pub err: ErrorCode,
+    /// The length of the returned data.
+    pub len: usize,
+    /// The returned data was truncated to fit the buffer.
+    pub truncated: bool,
+    /// The original destination of the message.
+    pub dest: Destination,
+}
+
+impl Rejected {
+    /// The service range which the message was sent to, if any.
+    pub fn service(&self) -> Option<ServiceRange> {
+        match self.dest {
+            Destination::Service(service) => Some(service),
+            Destination::Socket(_) => None,
+        }
+    }
+}
+
+impl ErrorCode {
+    /// Returns the error code reported by the `io::Error`, if any.
+    pub fn from_io_error(err: &io::Error) -> Option<Self> {
+        err.get_ref()
+            .and_then(|err| err.downcast_ref::<ErrorCode>())
+            .cloned()
+    }
+
+    /// Returns the error of a connection aborted with the code,
+    /// or `None` if the connection was shut down by the peer.
+    pub(crate) fn into_conn_error(self) -> Option<io::Error> {
+        if self == ErrorCode::ConnShutdown {
+            None
+        } else {
+            Some(self.into())
+        }
+    }
+}
 
 /// A TIPC socket.
 #[repr(transparent)]
@@ -843,6 +1083,12 @@ impl Deref for Socket {
     }
 }
 
+impl AsRef<Socket> for Socket {
+    fn as_ref(&self) -> &Socket {
+        self
+    }
+}
+
 /// Constructs a new `Datagram` with the `AF_TIPC` domain, the `SOCK_RDM` type.
 ///
 /// Provides a reliable datagram layer that does not guarantee ordering.
@@ -879,20 +1125,25 @@ pub fn new(sock_type: i32) -> io::Result<Socket> {
         .map(Socket)
 }
 
+/// Moves the file descriptor into or out of nonblocking mode.
+pub fn set_nonblocking(fd: RawFd, nonblocking: bool) -> io::Result<()> {
+    unsafe {
+        let mut flags: i32 = libc::fcntl(fd, libc::F_GETFL, 0).into_result()?;
+
+        if nonblocking {
+            flags |= libc::O_NONBLOCK;
+        } else {
+            flags &= !libc::O_NONBLOCK;
+        }
+
+        libc::fcntl(fd, libc::F_SETFL, flags).into_result()
+    }
+}
+
 impl Socket {
     /// Moves this TIPC stream into or out of nonblocking mode.
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
-        unsafe {
-            let mut flags: i32 = libc::fcntl(self.as_raw_fd(), libc::F_GETFL, 0).into_result()?;
-
-            if nonblocking {
-                flags |= libc::O_NONBLOCK;
-            } else {
-                flags &= !libc::O_NONBLOCK;
-            }
-
-            libc::fcntl(self.as_raw_fd(), libc::F_SETFL, flags).into_result()
-        }
+        set_nonblocking(self.as_raw_fd(), nonblocking)
     }
 
     /// Get the message importance levels.
@@ -928,6 +1179,20 @@ impl Socket {
         )
     }
 
+    /// Forces the multicast messages to be sent by broadcast.
+    ///
+    /// Otherwise the kernel selects between broadcast and replicast,
+    /// based on the number of the destination nodes.
+    pub fn set_mcast_broadcast(&self) -> io::Result<()> {
+        self.set_sock_flag(libc::SOL_TIPC, ffi::TIPC_MCAST_BROADCAST)
+    }
+
+    /// Forces the multicast messages to be sent by replicast,
+    /// a unicast copy to each destination node.
+    pub fn set_mcast_replicast(&self) -> io::Result<()> {
+        self.set_sock_flag(libc::SOL_TIPC, ffi::TIPC_MCAST_REPLICAST)
+    }
+
     /// Returns an error representing the last socket error which occurred.
     pub fn last_error(&self) -> io::Error {
         match self.get_sock_opt::<libc::socklen_t>(libc::SOL_SOCKET, libc::SO_ERROR as u32) {
@@ -946,6 +1211,35 @@ impl Socket {
         self.set_sock_opt(libc::SOL_SOCKET, libc::SO_RCVBUF as u32, size)
     }
 
+    /// Sets the read timeout, the receive calls fail with `WouldBlock` after the timeout.
+    ///
+    /// The receive calls block indefinitely if the timeout is `None`.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        let tv = match timeout {
+            Some(timeout) if timeout == Duration::default() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "cannot set a 0 duration timeout",
+                ))
+            }
+            Some(timeout) => {
+                // round up, so that a sub-microsecond timeout doesn't block indefinitely
+                let micros = timeout.as_micros() + u128::from(timeout.subsec_nanos() % 1000 != 0);
+
+                libc::timeval {
+                    tv_sec: (micros / 1_000_000).min(libc::time_t::MAX as u128) as libc::time_t,
+                    tv_usec: (micros % 1_000_000) as libc::suseconds_t,
+                }
+            }
+            None => libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+        };
+
+        self.set_sock_opt(libc::SOL_SOCKET, libc::SO_RCVTIMEO as u32, tv)
+    }
+
     /// Get the current value of a socket option.
     pub fn get_sock_opt<T>(&self, level: i32, opt: u32) -> io::Result<T> {
         let mut buf = MaybeUninit::<T>::zeroed();
@@ -978,6 +1272,12 @@ impl Socket {
         .into_result()
     }
 
+    /// Set a socket option which takes no value.
+    pub fn set_sock_flag(&self, level: i32, opt: u32) -> io::Result<()> {
+        unsafe { libc::setsockopt(self.as_raw_fd(), level, opt as i32, ptr::null(), 0) }
+            .into_result()
+    }
+
     /// Returns the address of the local half of this TIPC socket.
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
         let mut sa = MaybeUninit::<ffi::sockaddr_tipc>::uninit();
@@ -1161,22 +1461,19 @@ impl Socket {
     where
         A: ToSocketAddrs,
     {
-        let addr = addr
-            .to_socket_addrs()?
-            .next()
-            .ok_or_else(addr_not_available)?
-            .into();
-        let msg = libc::msghdr {
-            msg_name: &addr as *const _ as *mut _,
-            msg_namelen: mem::size_of::<ffi::sockaddr_tipc>() as u32,
-            msg_iov: bufs.as_ptr() as *const _ as *mut _,
-            msg_iovlen: bufs.len(),
-            msg_control: ptr::null_mut(),
-            msg_controllen: 0,
-            msg_flags: 0,
-        };
+        self.send_msg(bufs).to(addr).flags(flags).send()
+    }
 
-        unsafe { libc::sendmsg(self.as_raw_fd(), &msg, flags.bits()) }.into_result()
+    /// Builds a message gathered from a slice of buffers.
+    ///
+    /// The message is sent through the connection, unless a destination is given with `SendMsg::to`.
+    pub fn send_msg<'a>(&'a self, bufs: &'a [io::IoSlice<'a>]) -> SendMsg<'a> {
+        SendMsg {
+            sock: self,
+            bufs,
+            dst: Ok(None),
+            flags: Send::empty(),
+        }
     }
 
     /// Receives data from the socket.
@@ -1199,17 +1496,17 @@ impl Socket {
     /// Receives data from the socket.
     ///
     /// On success, returns the number of bytes read and the address from whence the data came.
+    /// If the message didn't fit the buffer, an `InvalidData` error is returned with `Truncated`.
     pub fn recv_from<T: AsMut<[u8]>>(
         &self,
         buf: T,
         flags: Recv,
     ) -> io::Result<(usize, SocketAddr)> {
         match self.recv_msg(buf, flags)? {
-            (RecvMsg::Message { len, .. }, addr) => Ok((len, addr)),
-            (RecvMsg::Rejected { err, .. }, _) => Err(io::Error::new(
-                io::ErrorKind::Other,
-                Error::from(Rejected(err)),
-            )),
+            (RecvMsg::Message { len, truncated, .. }, addr) => {
+                Truncated::check(len, truncated, flags).map(|len| (len, addr))
+            }
+            (RecvMsg::Rejected(rejected), _) => Err(rejected.err.into()),
             (msg, _) => Err(io::Error::new(
                 io::ErrorKind::Other,
                 format_err!("unexpected group event: {:?}", msg),
@@ -1221,6 +1518,7 @@ impl Socket {
     ///
     /// Data is copied to fill each buffer in order, with the final buffer written to possibly being only partially filled.
     /// This method must behave as a single call to `recv_from` with the buffers concatenated would.
+    /// If the message didn't fit the buffers, an `InvalidData` error is returned with `Truncated`.
     pub fn recv_from_vectored(
         &self,
         bufs: &mut [io::IoSliceMut],
@@ -1240,6 +1538,11 @@ impl Socket {
 
         let len =
             unsafe { libc::recvmsg(self.as_raw_fd(), &mut msg, flags.bits()) }.into_result()?;
+        let len = Truncated::check(
+            len,
+            (msg.msg_flags & libc::MSG_TRUNC) == libc::MSG_TRUNC,
+            flags,
+        )?;
 
         let sender = unsafe { sender.assume_init().addr.id.into() };
         let dest_name = unsafe { cmsgs(&msg) }
@@ -1255,106 +1558,160 @@ impl Socket {
         Ok((len, sender, dest_name))
     }
 
+    /// Receives a message, a returned undelivered message or a group membership event.
     pub fn recv_msg<T: AsMut<[u8]>>(
         &self,
         mut buf: T,
         flags: Recv,
     ) -> io::Result<(RecvMsg, SocketAddr)> {
-        let buf = buf.as_mut();
-        let mut addr = MaybeUninit::<[ffi::sockaddr_tipc; 2]>::zeroed();
-        let addr_len = mem::size_of_val(&addr) as u32;
-        let iov = libc::iovec {
-            iov_base: buf.as_mut_ptr() as *mut _,
-            iov_len: buf.len(),
-        };
-        let anc_space_size =
-            unsafe { libc::CMSG_SPACE(8) + libc::CMSG_SPACE(1024) + libc::CMSG_SPACE(16) };
-        let mut anc_space = vec![0u8; anc_space_size as usize];
-        let mut msg = libc::msghdr {
-            msg_name: addr.as_mut_ptr() as *mut _,
-            msg_namelen: addr_len,
-            msg_iov: &iov as *const _ as *mut _,
-            msg_iovlen: 1,
-            msg_control: anc_space.as_mut_ptr() as *mut _,
-            msg_controllen: anc_space.len(),
-            msg_flags: 0,
-        };
+        self.recv_msg_vectored(
+            &mut [io::IoSliceMut::new(buf.as_mut())],
+            &mut RecvMsgBuf::new(),
+            flags,
+        )
+    }
+
+    /// Like `recv_msg`, except that it receives into a slice of buffers,
+    /// with the address and control buffers owned by the caller and reused across the calls.
+    ///
+    /// The data of a returned undelivered message is copied to fill each buffer in order.
+    pub fn recv_msg_vectored(
+        &self,
+        bufs: &mut [io::IoSliceMut],
+        msg_buf: &mut RecvMsgBuf,
+        flags: Recv,
+    ) -> io::Result<(RecvMsg, SocketAddr)> {
+        let mut msg = msg_buf.msghdr(bufs);
 
         let len =
             unsafe { libc::recvmsg(self.as_raw_fd(), &mut msg, flags.bits()) }.into_result()?;
 
-        let addr = unsafe { addr.assume_init() };
+        msg_buf.parse(self, &msg, len, bufs)
+    }
 
-        // Add source addresses
-        let member_id = if msg.msg_namelen == addr_len {
-            Some(unsafe { addr[1].addr.name.name.into() })
-        } else {
-            None
+    /// Returns the length of the next message, without removing it from the queue.
+    ///
+    /// It blocks until a message is received, unless the socket is in the non-blocking mode.
+    /// The length of the returned data is reported for a rejected message, and `None` for a membership event.
+    pub fn next_message_len(&self) -> io::Result<Option<usize>> {
+        // The kernel only reports the length copied, so the message is peeked into a buffer
+        // large enough, which is left uninitialized since it is never read.
+        let mut data = Vec::<u8>::with_capacity(ffi::TIPC_MAX_USER_MSG_SIZE as usize);
+        let mut iov = libc::iovec {
+            iov_base: data.as_mut_ptr() as *mut _,
+            iov_len: data.capacity(),
         };
-        let sock_id = unsafe { addr[0].addr.id.into() };
+        let mut msg_buf = RecvMsgBuf::new();
+        let mut msg = msg_buf.msghdr(&mut []);
 
-        // Handle group member event
-        if (msg.msg_flags & libc::MSG_OOB) == libc::MSG_OOB {
-            if len != 0 {
-                Err(io::Error::new(io::ErrorKind::Other, "unexpected OOB data"))
-            } else {
-                let event = if (msg.msg_flags & libc::MSG_EOR) == libc::MSG_EOR {
-                    RecvMsg::MemberLeave(member_id.unwrap())
-                } else {
-                    RecvMsg::MemberJoin(member_id.unwrap())
-                };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
 
-                Ok((event, sock_id))
-            }
-        } else {
-            let mut err = None;
-            let mut err_len = None;
-            let mut service = None;
+        let len =
+            unsafe { libc::recvmsg(self.as_raw_fd(), &mut msg, libc::MSG_PEEK) }.into_result()?;
 
-            for (ty, level, data) in unsafe { cmsgs(&msg) } {
-                if level == libc::SOL_TIPC {
-                    match ty as u32 {
-                        ffi::TIPC_ERRINFO if data.len() == mem::size_of::<u32>() * 2 => {
-                            let mut chunks = data.chunks_exact(mem::size_of::<u32>());
+        msg_buf
+            .parse(self, &msg, len, &mut [])
+            .map(|(msg, _)| match msg {
+                RecvMsg::MemberJoin(_) | RecvMsg::MemberLeave(_) => None,
+                _ => Some(msg.data_len()),
+            })
+    }
 
-                            err = chunks
-                                .next()
-                                .unwrap()
-                                .try_into()
-                                .map(u32::from_ne_bytes)
-                                .ok();
-                            err_len = chunks
-                                .next()
-                                .unwrap()
-                                .try_into()
-                                .map(u32::from_ne_bytes)
-                                .map(|n| n as usize)
-                                .ok();
-                        }
-                        ffi::TIPC_RETDATA if Some(data.len()) == err_len => {
-                            let len = buf.len().min(err_len.unwrap());
-                            let buf = &mut buf[..len];
+    /// Receives multiple messages with a single system call, blocking until at least one is received.
+    ///
+    /// The messages are received into the buffers, up to the number of the address and control buffers
+    /// and at most `MAX_BATCH` messages. On success, returns the number of the messages received,
+    /// each one is reported by `RecvMsgBuf::received` of its address and control buffers,
+    /// or by `RecvMsgBuf::error` if it couldn't be parsed, without failing the others.
+    pub fn recv_batch<B: AsMut<[u8]>>(
+        &self,
+        bufs: &mut [B],
+        msg_bufs: &mut [RecvMsgBuf],
+        flags: Recv,
+    ) -> io::Result<usize> {
+        let mut iovs: [MaybeUninit<io::IoSliceMut>; MAX_BATCH] =
+            unsafe { MaybeUninit::uninit().assume_init() };
+        let mut hdrs: [libc::mmsghdr; MAX_BATCH] = unsafe { mem::zeroed() };
+        let mut vlen = 0;
+
+        for ((iov, buf), (hdr, msg_buf)) in iovs
+            .iter_mut()
+            .zip(bufs.iter_mut())
+            .zip(hdrs.iter_mut().zip(msg_bufs.iter_mut()))
+        {
+            let iov = iov.write(io::IoSliceMut::new(buf.as_mut()));
+
+            hdr.msg_hdr = msg_buf.msghdr(slice::from_mut(iov));
+            msg_buf.received = None;
+            vlen += 1;
+        }
 
-                            buf.copy_from_slice(&data[..len]);
-                        }
-                        ffi::TIPC_DESTNAME
-                            if data.len() == mem::size_of::<ffi::tipc_name_seq>() =>
-                        {
-                            service = NonNull::new(data.as_ptr() as *mut u8)
-                                .map(|p| p.cast::<ffi::tipc_name_seq>())
-                                .map(|p| unsafe { p.as_ptr().read() }.into());
-                        }
-                        _ => {}
-                    }
-                }
-            }
+        let n: i32 = unsafe {
+            libc::recvmmsg(
+                self.as_raw_fd(),
+                hdrs.as_mut_ptr(),
+                vlen,
+                flags.bits() | libc::MSG_WAITFORONE,
+                ptr::null_mut(),
+            )
+        }
+        .into_result()?;
+
+        for ((iov, hdr), msg_buf) in iovs
+            .iter_mut()
+            .zip(hdrs.iter())
+            .zip(msg_bufs.iter_mut())
+            .take(n as usize)
+        {
+            let iov = unsafe { iov.assume_init_mut() };
+
+            msg_buf.received = Some(msg_buf.parse(
+                self,
+                &hdr.msg_hdr,
+                hdr.msg_len as usize,
+                slice::from_mut(iov),
+            ));
+        }
 
-            if let Some(err) = err {
-                Ok((RecvMsg::Rejected { err, service }, self.local_addr()?))
-            } else {
-                Ok((RecvMsg::Message { len, service }, sock_id))
-            }
+        Ok(n as usize)
+    }
+
+    /// Sends multiple messages, each to its own destination, with a single system call.
+    ///
+    /// At most `MAX_BATCH` messages are sent, on success, returns the number of messages sent.
+    pub fn send_batch<B, A>(&self, msgs: &[(B, A)], flags: Send) -> io::Result<usize>
+    where
+        B: AsRef<[u8]>,
+        A: ToSocketAddrs,
+    {
+        let mut addrs = [ffi::sockaddr_tipc::default(); MAX_BATCH];
+        let mut iovs: [MaybeUninit<io::IoSlice>; MAX_BATCH] =
+            unsafe { MaybeUninit::uninit().assume_init() };
+        let mut hdrs: [libc::mmsghdr; MAX_BATCH] = unsafe { mem::zeroed() };
+        let mut vlen = 0;
+
+        for ((buf, dst), ((addr, iov), hdr)) in msgs
+            .iter()
+            .zip(addrs.iter_mut().zip(iovs.iter_mut()).zip(hdrs.iter_mut()))
+        {
+            *addr = dst
+                .to_socket_addrs()?
+                .next()
+                .map(Into::into)
+                .ok_or_else(addr_not_available)?;
+            let iov = iov.write(io::IoSlice::new(buf.as_ref()));
+
+            hdr.msg_hdr.msg_name = addr as *mut _ as *mut _;
+            hdr.msg_hdr.msg_namelen = mem::size_of_val(addr) as u32;
+            hdr.msg_hdr.msg_iov = iov as *mut _ as *mut _;
+            hdr.msg_hdr.msg_iovlen = 1;
+            vlen += 1;
         }
+
+        unsafe { libc::sendmmsg(self.as_raw_fd(), hdrs.as_mut_ptr(), vlen, flags.bits()) }
+            .into_result()
+            .map(|n: i32| n as usize)
     }
 
     /// Join a communication group.
@@ -1412,6 +1769,52 @@ impl Socket {
     }
 }
 
+/// A message to be sent, built with `Socket::send_msg`.
+pub struct SendMsg<'a> {
+    sock: &'a Socket,
+    bufs: &'a [io::IoSlice<'a>],
+    dst: io::Result<Option<ffi::sockaddr_tipc>>,
+    flags: Send,
+}
+
+impl<'a> SendMsg<'a> {
+    /// Sends the message to the destination.
+    ///
+    /// It may be a socket address, a service address with the optional lookup scope,
+    /// or a service range to multicast the message to all the matching sockets.
+    pub fn to<A: ToSocketAddrs>(mut self, dst: A) -> Self {
+        self.dst = dst
+            .to_socket_addrs()
+            .and_then(|mut addrs| addrs.next().ok_or_else(addr_not_available))
+            .map(|addr| Some(addr.into()));
+        self
+    }
+
+    /// Sends the message with the flags.
+    pub fn flags(mut self, flags: Send) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sends the message. On success, returns the number of bytes written.
+    pub fn send(self) -> io::Result<usize> {
+        let dst = self.dst?;
+        let msg = libc::msghdr {
+            msg_name: dst
+                .as_ref()
+                .map_or(ptr::null_mut(), |sa| sa as *const _ as *mut _),
+            msg_namelen: dst.map_or(0, |sa| mem::size_of_val(&sa) as u32),
+            msg_iov: self.bufs.as_ptr() as *const _ as *mut _,
+            msg_iovlen: self.bufs.len(),
+            msg_control: ptr::null_mut(),
+            msg_controllen: 0,
+            msg_flags: 0,
+        };
+
+        unsafe { libc::sendmsg(self.sock.as_raw_fd(), &msg, self.flags.bits()) }.into_result()
+    }
+}
+
 bitflags! {
     /// Flags for `join`.
     pub struct Join: u32 {
@@ -1575,6 +1978,182 @@ where
     }
 }
 
+/// The length of the control messages space.
+const fn cmsg_space(len: usize) -> usize {
+    let align = mem::size_of::<usize>();
+
+    mem::size_of::<libc::cmsghdr>() + ((len + align - 1) & !(align - 1))
+}
+
+/// The space of the `TIPC_ERRINFO`, `TIPC_RETDATA` and `TIPC_DESTNAME` control messages.
+const RECV_CONTROL_SIZE: usize = cmsg_space(mem::size_of::<u32>() * 2)
+    + cmsg_space(MAX_REJECTED_DATA)
+    + cmsg_space(mem::size_of::<ffi::tipc_name_seq>());
+
+#[repr(C, align(8))]
+#[derive(Clone, Copy)]
+struct Control([u8; RECV_CONTROL_SIZE]);
+
+/// The maximum number of messages sent or received by a batch.
+pub const MAX_BATCH: usize = 64;
+
+/// The address and control buffers to receive messages.
+///
+/// It is owned by the caller and reused to receive the messages without allocation.
+pub struct RecvMsgBuf {
+    addr: [ffi::sockaddr_tipc; 2],
+    control: Control,
+    received: Option<io::Result<(RecvMsg, SocketAddr)>>,
+}
+
+impl Default for RecvMsgBuf {
+    fn default() -> Self {
+        RecvMsgBuf::new()
+    }
+}
+
+impl fmt::Debug for RecvMsgBuf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RecvMsgBuf")
+            .field("received", &self.received)
+            .finish()
+    }
+}
+
+impl RecvMsgBuf {
+    /// Creates the buffers.
+    pub fn new() -> Self {
+        RecvMsgBuf {
+            addr: [ffi::sockaddr_tipc::default(); 2],
+            control: Control([0; RECV_CONTROL_SIZE]),
+            received: None,
+        }
+    }
+
+    /// The message received into the buffers by the last `recv_batch`, and where it came from.
+    pub fn received(&self) -> Option<&(RecvMsg, SocketAddr)> {
+        self.received.as_ref().and_then(|res| res.as_ref().ok())
+    }
+
+    /// The error of the message received into the buffers by the last `recv_batch`,
+    /// which couldn't be parsed.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.received.as_ref().and_then(|res| res.as_ref().err())
+    }
+
+    /// The message header receiving into the buffers.
+    pub(crate) fn msghdr(&mut self, bufs: &mut [io::IoSliceMut]) -> libc::msghdr {
+        libc::msghdr {
+            msg_name: self.addr.as_mut_ptr() as *mut _,
+            msg_namelen: mem::size_of_val(&self.addr) as u32,
+            msg_iov: bufs.as_mut_ptr() as *mut _,
+            msg_iovlen: bufs.len(),
+            msg_control: self.control.0.as_mut_ptr() as *mut _,
+            msg_controllen: RECV_CONTROL_SIZE,
+            msg_flags: 0,
+        }
+    }
+
+    /// Parses the message of `len` bytes received with the header.
+    pub(crate) fn parse(
+        &self,
+        sock: &Socket,
+        msg: &libc::msghdr,
+        len: usize,
+        bufs: &mut [io::IoSliceMut],
+    ) -> io::Result<(RecvMsg, SocketAddr)> {
+        // Add source addresses
+        let member_id = if msg.msg_namelen as usize == mem::size_of_val(&self.addr) {
+            Some(unsafe { self.addr[1].addr.name.name.into() })
+        } else {
+            None
+        };
+        let sock_id = unsafe { self.addr[0].addr.id.into() };
+
+        // Handle group member event
+        if (msg.msg_flags & libc::MSG_OOB) == libc::MSG_OOB {
+            if len != 0 {
+                Err(io::Error::new(io::ErrorKind::Other, "unexpected OOB data"))
+            } else {
+                let event = if (msg.msg_flags & libc::MSG_EOR) == libc::MSG_EOR {
+                    RecvMsg::MemberLeave(member_id.unwrap())
+                } else {
+                    RecvMsg::MemberJoin(member_id.unwrap())
+                };
+
+                Ok((event, sock_id))
+            }
+        } else {
+            let mut err = None;
+            let mut err_len = 0;
+            let mut service = None;
+
+            for (ty, level, data) in unsafe { cmsgs(msg) } {
+                if level == libc::SOL_TIPC {
+                    match ty as u32 {
+                        ffi::TIPC_ERRINFO if data.len() == mem::size_of::<u32>() * 2 => {
+                            let mut chunks = data.chunks_exact(mem::size_of::<u32>());
+
+                            err = chunks
+                                .next()
+                                .unwrap()
+                                .try_into()
+                                .map(u32::from_ne_bytes)
+                                .ok();
+                            err_len = chunks
+                                .next()
+                                .unwrap()
+                                .try_into()
+                                .map(u32::from_ne_bytes)
+                                .map(|n| n as usize)
+                                .unwrap_or_default();
+                        }
+                        ffi::TIPC_RETDATA => {
+                            let mut data = data;
+
+                            for buf in bufs.iter_mut() {
+                                let len = buf.len().min(data.len());
+
+                                buf[..len].copy_from_slice(&data[..len]);
+                                data = &data[len..];
+                            }
+                        }
+                        ffi::TIPC_DESTNAME
+                            if data.len() == mem::size_of::<ffi::tipc_name_seq>() =>
+                        {
+                            service = NonNull::new(data.as_ptr() as *mut u8)
+                                .map(|p| p.cast::<ffi::tipc_name_seq>())
+                                .map(|p| unsafe { p.as_ptr().read_unaligned() }.into());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            if let Some(err) = err {
+                // The returned message is reversed, its origin is the original destination.
+                let rejected = Rejected {
+                    err: err.into(),
+                    len: err_len,
+                    truncated: err_len > bufs.iter().map(|buf| buf.len()).sum(),
+                    dest: service.map_or(Destination::Socket(sock_id), Destination::Service),
+                };
+
+                Ok((RecvMsg::Rejected(rejected), sock.local_addr()?))
+            } else {
+                Ok((
+                    RecvMsg::Message {
+                        len,
+                        service,
+                        truncated: (msg.msg_flags & libc::MSG_TRUNC) == libc::MSG_TRUNC,
+                    },
+                    sock_id,
+                ))
+            }
+        }
+    }
+}
+
 /// Received message from a TIPC socket.
 #[derive(Clone, Debug)]
 pub enum RecvMsg {
@@ -1582,16 +2161,28 @@ pub enum RecvMsg {
     MemberJoin(ServiceAddr),
     /// Reception of a membership event.
     MemberLeave(ServiceAddr),
-    /// A returned undelivered data message.
+    /// A data message.
     Message {
+        /// The length of the data received into the buffer.
         len: usize,
+        /// The service address or range which the message was sent to, if any.
         service: Option<ServiceRange>,
+        /// The message was truncated to fit the buffer, and the rest was discarded unless peeked.
+        truncated: bool,
     },
-    /// The message was rejected
-    Rejected {
-        err: u32,
-        service: Option<ServiceRange>,
-    },
+    /// The message was rejected, and its data was returned into the buffer.
+    Rejected(Rejected),
+}
+
+impl RecvMsg {
+    /// The length of the data received into the buffer.
+    pub fn data_len(&self) -> usize {
+        match self {
+            RecvMsg::Message { len, .. } => *len,
+            RecvMsg::Rejected(rejected) => rejected.len,
+            RecvMsg::MemberJoin(_) | RecvMsg::MemberLeave(_) => 0,
+        }
+    }
 }
 
 pub trait IntoResult<T> {
@@ -1634,3 +2225,77 @@ where
         self.into_result().map(|_: T| ())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::addr::ServiceRange;
+
+    const SERVICE_TYPE: u32 = 18888;
+
+    /// Requires the `tipc` module.
+    ///
+    /// The kernel doesn't report the multicast method, the messages must be delivered with either of them.
+    #[test]
+    #[ignore]
+    fn mcast_method() {
+        let range = ServiceRange::new(SERVICE_TYPE, 0, 9);
+        let srv = rdm().unwrap().bind(range).unwrap();
+        let cli = rdm().unwrap();
+        let mut buf = [0; 16];
+
+        // the options don't accept a value
+        assert_eq!(
+            cli.as_ref()
+                .set_sock_opt(libc::SOL_TIPC, ffi::TIPC_MCAST_BROADCAST, 0u32)
+                .unwrap_err()
+                .raw_os_error(),
+            Some(libc::EINVAL)
+        );
+
+        cli.as_ref().set_mcast_broadcast().unwrap();
+        cli.send_to(b"broadcast", range).unwrap();
+
+        let (len, _) = srv.recv_from(&mut buf[..]).unwrap();
+        assert_eq!(&buf[..len], b"broadcast");
+
+        cli.as_ref().set_mcast_replicast().unwrap();
+        cli.send_to(b"replicast", range).unwrap();
+
+        let (len, _) = srv.recv_from(&mut buf[..]).unwrap();
+        assert_eq!(&buf[..len], b"replicast");
+    }
+
+    /// Requires the `tipc` module.
+    #[test]
+    #[ignore]
+    fn truncated() {
+        let srv = rdm().unwrap();
+        let cli = rdm().unwrap();
+        let dst = srv.local_addr().unwrap();
+        let data = vec![0xAA; 1000];
+        let mut buf = [0; 100];
+
+        cli.send_to(&data, dst).unwrap();
+        cli.send_to(&data, dst).unwrap();
+
+        assert_eq!(srv.next_message_len().unwrap(), Some(data.len()));
+
+        let err = srv.recv_from(&mut buf[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<Truncated>(),
+            Some(&Truncated { len: buf.len() })
+        );
+
+        let (head, tail) = buf.split_at_mut(50);
+        let err = srv
+            .recv_from_vectored(&mut [io::IoSliceMut::new(head), io::IoSliceMut::new(tail)])
+            .unwrap_err();
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<Truncated>(),
+            Some(&Truncated { len: buf.len() })
+        );
+    }
+}
@@ -1,9 +1,11 @@
 //! The TIPC internal topology service.
 
+use core::fmt;
 use core::mem::{self, MaybeUninit};
 use core::ops::Deref;
 use core::time::Duration;
 
+use std::error;
 use std::io;
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 
@@ -230,9 +232,16 @@ impl Server {
 
     /// Receives events for this service range.
     pub fn recv(&self) -> io::Result<Event> {
+        self.recv_event()?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "receive event failed"))
+    }
+
+    /// Receives events for this service range,
+    /// returns `None` if the topology server has closed the connection.
+    pub(crate) fn recv_event(&self) -> io::Result<Option<Event>> {
         let mut evt = MaybeUninit::<ffi::tipc_event>::zeroed();
 
-        unsafe {
+        let size = unsafe {
             libc::recv(
                 self.0.as_raw_fd(),
                 evt.as_mut_ptr() as *mut _,
@@ -240,14 +249,13 @@ impl Server {
                 0,
             )
         }
-        .into_result()
-        .and_then(|size| {
-            if size == mem::size_of::<ffi::tipc_event>() {
-                Ok(())
-            } else {
-                Err(io::Error::new(io::ErrorKind::Other, "receive event failed"))
-            }
-        })?;
+        .into_result()?;
+
+        if size == 0 {
+            return Ok(None);
+        } else if size != mem::size_of::<ffi::tipc_event>() {
+            return Err(io::Error::new(io::ErrorKind::Other, "receive event failed"));
+        }
 
         let evt = unsafe { evt.assume_init() };
 
@@ -256,7 +264,7 @@ impl Server {
                 // The subscription expired, as specified by the given timeout value, and has been removed.
                 Err(io::Error::new(
                     io::ErrorKind::TimedOut,
-                    "receive event timed out",
+                    Expired(evt.s.into()),
                 ))
             }
             ffi::TIPC_PUBLISHED | ffi::TIPC_WITHDRAWN => {
@@ -265,17 +273,17 @@ impl Server {
                 let subscription = evt.s.into();
 
                 if evt.event == ffi::TIPC_PUBLISHED {
-                    Ok(Event::Published {
+                    Ok(Some(Event::Published {
                         service,
                         sock,
                         subscription,
-                    })
+                    }))
                 } else {
-                    Ok(Event::Withdrawn {
+                    Ok(Some(Event::Withdrawn {
                         service,
                         sock,
                         subscription,
-                    })
+                    }))
                 }
             }
             _ => Err(io::Error::new(
@@ -286,6 +294,20 @@ impl Server {
     }
 }
 
+/// The subscription expired, as specified by the given timeout value, and has been removed.
+///
+/// It is reported as the inner error of an `io::ErrorKind::TimedOut` error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Expired(pub Subscription);
+
+impl fmt::Display for Expired {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "subscription to {} timed out", self.0.service)
+    }
+}
+
+impl error::Error for Expired {}
+
 /// The service event.
 #[derive(Debug)]
 pub enum Event {
@@ -351,21 +373,21 @@ impl Event {
     }
 }
 
-/// An iterator over the events.
+/// An iterator over the events, ends when the topology server has closed the connection.
 #[repr(transparent)]
 #[derive(Debug)]
 pub struct Events<'a>(&'a Server);
 
 impl<'a> Iterator for Events<'a> {
-    type Item = Event;
+    type Item = io::Result<Event>;
 
-    fn next(&mut self) -> Option<Event> {
-        self.0.recv().ok()
+    fn next(&mut self) -> Option<io::Result<Event>> {
+        self.0.recv_event().transpose()
     }
 }
 
 impl<'a> IntoIterator for &'a Server {
-    type Item = Event;
+    type Item = io::Result<Event>;
     type IntoIter = Events<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -418,7 +440,7 @@ impl Node {
     }
 }
 
-/// An iterator over the node events.
+/// An iterator over the node events, ends when the topology server has closed the connection.
 #[repr(transparent)]
 #[derive(Debug)]
 pub struct Nodes(Server);
@@ -426,10 +448,10 @@ pub struct Nodes(Server);
 impl_raw_fd_traits! { Nodes(Server) }
 
 impl Iterator for Nodes {
-    type Item = Node;
+    type Item = io::Result<Node>;
 
-    fn next(&mut self) -> Option<Node> {
-        self.recv().ok()
+    fn next(&mut self) -> Option<io::Result<Node>> {
+        self.recv_event().transpose()
     }
 }
 
@@ -437,6 +459,10 @@ impl Nodes {
     pub fn recv(&self) -> io::Result<Node> {
         self.0.recv().map(Node::from)
     }
+
+    pub(crate) fn recv_event(&self) -> io::Result<Option<Node>> {
+        self.0.recv_event().map(|evt| evt.map(Node::from))
+    }
 }
 
 /// Subscribe events for neighbor links.
@@ -531,7 +557,7 @@ impl Link {
     }
 }
 
-/// An iterator over the link events.
+/// An iterator over the link events, ends when the topology server has closed the connection.
 #[repr(transparent)]
 #[derive(Debug)]
 pub struct Links(Server);
@@ -539,10 +565,10 @@ pub struct Links(Server);
 impl_raw_fd_traits! { Links(Server) }
 
 impl Iterator for Links {
-    type Item = Link;
+    type Item = io::Result<Link>;
 
-    fn next(&mut self) -> Option<Link> {
-        self.recv().ok()
+    fn next(&mut self) -> Option<io::Result<Link>> {
+        self.recv_event().transpose()
     }
 }
 
@@ -550,6 +576,10 @@ impl Links {
     pub fn recv(&self) -> io::Result<Link> {
         self.0.recv().map(Link::from)
     }
+
+    pub(crate) fn recv_event(&self) -> io::Result<Option<Link>> {
+        self.0.recv_event().map(|evt| evt.map(Link::from))
+    }
 }
 
 /// Retrieve a link name
@@ -0,0 +1,186 @@
+//! The traits abstracting over the kernel TIPC sockets and the alternative backends.
+//!
+//! The application code written against these traits runs on the sockets of this crate,
+//! or on any other transport implementing them, e.g. the simulated sockets of the `mock` feature.
+
+use core::time::Duration;
+
+use std::io;
+
+use crate::{
+    addr::SocketAddr,
+    sock::{
+        Bound, Connectable, Connected, Datagram, Group, Recv, RecvMsg, SeqPacket, ToSocketAddrs,
+    },
+    topo::{self, Subscription},
+};
+
+/// A connectionless socket sending and receiving messages.
+pub trait MessageSocket {
+    /// Returns the address of the local half of this socket.
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+
+    /// Sends data on the socket to the given address. On success, returns the number of bytes written.
+    fn send_to<B, A>(&self, buf: B, dst: A) -> io::Result<usize>
+    where
+        B: AsRef<[u8]>,
+        A: ToSocketAddrs;
+
+    /// Receives data from the socket.
+    ///
+    /// On success, returns the number of bytes read and the address from whence the data came.
+    fn recv_from<B: AsMut<[u8]>>(&self, buf: B) -> io::Result<(usize, SocketAddr)>;
+
+    /// Receives a message, a returned undelivered message or a group membership event.
+    fn recv_msg<B: AsMut<[u8]>>(&self, buf: B, flags: Recv) -> io::Result<(RecvMsg, SocketAddr)>;
+
+    /// Sets the read timeout, the receive calls fail with `WouldBlock` after the timeout.
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+/// A connection-oriented socket.
+pub trait ConnectedSocket {
+    /// Get the socket address of the peer socket.
+    fn peer_addr(&self) -> io::Result<SocketAddr>;
+
+    /// Sends data on the socket to the remote address to which it is connected.
+    fn send<B: AsRef<[u8]>>(&self, buf: B) -> io::Result<usize>;
+
+    /// Receives data from the socket.
+    ///
+    /// On success, returns the number of bytes read.
+    fn recv<B: AsMut<[u8]>>(&self, buf: B) -> io::Result<usize>;
+
+    /// Shut down the read and write halves of this connection.
+    fn shutdown(&self) -> io::Result<()>;
+
+    /// Sets the read timeout, the receive calls fail with `WouldBlock` after the timeout.
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+/// A connection-oriented socket which keeps the message boundaries.
+pub trait SeqPacketSocket: ConnectedSocket {}
+
+/// A source of the topology events.
+pub trait TopologySource {
+    /// The subscriber wants `All` or `Edge` event for each matching update of the binding table.
+    fn subscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<Subscription>;
+
+    /// The subscriber doesn't want any more events for this service range.
+    fn unsubscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<()>;
+
+    /// Receives events for the subscriptions.
+    fn recv(&self) -> io::Result<topo::Event>;
+}
+
+macro_rules! impl_message_socket {
+    ($name:ident) => {
+        impl MessageSocket for $name {
+            fn local_addr(&self) -> io::Result<SocketAddr> {
+                $name::local_addr(self)
+            }
+
+            fn send_to<B, A>(&self, buf: B, dst: A) -> io::Result<usize>
+            where
+                B: AsRef<[u8]>,
+                A: ToSocketAddrs,
+            {
+                $name::send_to(self, buf, dst)
+            }
+
+            fn recv_from<B: AsMut<[u8]>>(&self, buf: B) -> io::Result<(usize, SocketAddr)> {
+                $name::recv_from(self, buf)
+            }
+
+            fn recv_msg<B: AsMut<[u8]>>(
+                &self,
+                buf: B,
+                flags: Recv,
+            ) -> io::Result<(RecvMsg, SocketAddr)> {
+                self.as_ref().recv_msg(buf, flags)
+            }
+
+            fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+                self.as_ref().set_read_timeout(timeout)
+            }
+        }
+    };
+}
+
+impl_message_socket!(Datagram);
+impl_message_socket!(SeqPacket);
+
+macro_rules! impl_deref_message_socket {
+    ($name:ident) => {
+        impl<T: MessageSocket> MessageSocket for $name<T> {
+            fn local_addr(&self) -> io::Result<SocketAddr> {
+                (**self).local_addr()
+            }
+
+            fn send_to<B, A>(&self, buf: B, dst: A) -> io::Result<usize>
+            where
+                B: AsRef<[u8]>,
+                A: ToSocketAddrs,
+            {
+                (**self).send_to(buf, dst)
+            }
+
+            fn recv_from<B: AsMut<[u8]>>(&self, buf: B) -> io::Result<(usize, SocketAddr)> {
+                (**self).recv_from(buf)
+            }
+
+            fn recv_msg<B: AsMut<[u8]>>(
+                &self,
+                buf: B,
+                flags: Recv,
+            ) -> io::Result<(RecvMsg, SocketAddr)> {
+                (**self).recv_msg(buf, flags)
+            }
+
+            fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+                (**self).set_read_timeout(timeout)
+            }
+        }
+    };
+}
+
+impl_deref_message_socket!(Bound);
+impl_deref_message_socket!(Group);
+
+impl<T: Connectable> ConnectedSocket for Connected<T> {
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        Connected::peer_addr(self)
+    }
+
+    fn send<B: AsRef<[u8]>>(&self, buf: B) -> io::Result<usize> {
+        Connected::send(self, buf)
+    }
+
+    fn recv<B: AsMut<[u8]>>(&self, buf: B) -> io::Result<usize> {
+        Connected::recv(self, buf)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        Connected::shutdown(self)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        (**self).as_ref().set_read_timeout(timeout)
+    }
+}
+
+impl SeqPacketSocket for Connected<SeqPacket> {}
+
+impl TopologySource for topo::Server {
+    fn subscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<Subscription> {
+        topo::Server::subscribe(self, sub)
+    }
+
+    fn unsubscribe<T: Into<Subscription>>(&self, sub: T) -> io::Result<()> {
+        topo::Server::unsubscribe(self, sub)
+    }
+
+    fn recv(&self) -> io::Result<topo::Event> {
+        topo::Server::recv(self)
+    }
+}